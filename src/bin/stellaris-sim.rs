@@ -0,0 +1,277 @@
+//! Run the game without graphics, for balance testing.
+//!
+//! The galaxy is generated from the given parameters, all empires are
+//! controlled by the AI, and a summary of every empire is printed after
+//! the requested number of ticks:
+//!
+//! `stellaris-sim --seed 42 --size 1024 --empires 4 --ticks 3000 --format csv`
+//!
+//! Options:
+//! - `--seed <n>`: the seed of the galaxy, random by default.
+//! - `--size <n>`: the number of planetary systems.
+//! - `--density <f>`: the density of planetary systems, in 1/ly^2.
+//! - `--empires <n>`: the number of empires, 4 by default.
+//! - `--ticks <n>`: the number of ticks to simulate, 1000 by default.
+//! - `--format <json|csv>`: the format of the summary, json by default.
+//! - `--output <path>`: write the summary to a file instead of stdout.
+
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::utils::HashMap;
+use serde::Serialize;
+use std::io::Write;
+use std::time::{Duration, Instant};
+use stellaris::empire::{
+    spawn_empires, Empire, EmpireSetup, Owner, Resources, Species, EMPIRE_COLORS,
+};
+use stellaris::fleet::Fleet;
+use stellaris::game_map::galaxy::gen::GalaxyGenParams;
+use stellaris::game_map::galaxy::{Galaxy, PrimaryGalaxy};
+use stellaris::game_map::gen::GampMapGenPlugin;
+use stellaris::game_map::planetary_system::PlanetarySystem;
+use stellaris::states::{AppState, LoadSource};
+use stellaris::tick::GameTick;
+use stellaris::CorePlugin;
+
+/// The longest time map generation may take.
+const LOADING_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Format {
+    Json,
+    Csv,
+}
+
+#[derive(Resource, Clone, Debug)]
+struct SimArgs {
+    params: GalaxyGenParams,
+    empires: usize,
+    ticks: u64,
+    format: Format,
+    output: Option<String>,
+}
+
+/// The usage printed on invalid arguments.
+const USAGE: &str = "usage: stellaris-sim [--seed <n>] [--size <n>] [--density <f>] \
+[--empires <n>] [--ticks <n>] [--format <json|csv>] [--output <path>]";
+
+/// Parse the value of an option.
+fn parse<T: std::str::FromStr>(flag: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("invalid value {:?} for {}", value, flag))
+}
+
+/// The options taking a value.
+const OPTIONS: [&str; 7] = [
+    "--seed",
+    "--size",
+    "--density",
+    "--empires",
+    "--ticks",
+    "--format",
+    "--output",
+];
+
+impl SimArgs {
+    /// Parse the options, or describe the first invalid one.
+    fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, String> {
+        // skip the name of the program.
+        let mut args = args.into_iter().skip(1);
+        let mut values = HashMap::new();
+        while let Some(flag) = args.next() {
+            if !OPTIONS.contains(&flag.as_str()) {
+                return Err(format!("unknown option {:?}", flag));
+            }
+            match args.next() {
+                Some(value) if !value.starts_with("--") => values.insert(flag, value),
+                _ => return Err(format!("missing value for {}", flag)),
+            };
+        }
+        let value = |flag: &str| values.get(flag);
+
+        let mut params = GalaxyGenParams::default();
+        if let Some(seed) = value("--seed") {
+            params.seed = parse("--seed", seed)?;
+        }
+        if let Some(size) = value("--size") {
+            params.size = parse("--size", size)?;
+            if !GalaxyGenParams::SIZE_RANGE.contains(&params.size) {
+                return Err(format!(
+                    "--size must be within {:?}",
+                    GalaxyGenParams::SIZE_RANGE
+                ));
+            }
+        }
+        if let Some(density) = value("--density") {
+            params.density = parse("--density", density)?;
+            if !GalaxyGenParams::DENSITY_RANGE.contains(&params.density) {
+                return Err(format!(
+                    "--density must be within {:?}",
+                    GalaxyGenParams::DENSITY_RANGE
+                ));
+            }
+        }
+        if !params.can_fill() {
+            return Err(format!(
+                "the galaxy cannot hold its systems at density {}",
+                params.density
+            ));
+        }
+
+        Ok(Self {
+            params,
+            empires: value("--empires").map_or(Ok(4), |n| parse("--empires", n))?,
+            ticks: value("--ticks").map_or(Ok(1000), |n| parse("--ticks", n))?,
+            format: match value("--format").map(String::as_str) {
+                None | Some("json") => Format::Json,
+                Some("csv") => Format::Csv,
+                Some(format) => {
+                    return Err(format!("unknown format {:?}, expect json or csv", format))
+                }
+            },
+            output: value("--output").cloned(),
+        })
+    }
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct EmpireStats {
+    name: String,
+    energy: f64,
+    systems: usize,
+    /// the number of fleets, there is no other measure of strength yet.
+    fleet_strength: usize,
+}
+
+#[derive(Clone, Debug, Serialize)]
+struct Summary {
+    seed: u128,
+    size: usize,
+    density: f32,
+    ticks: u64,
+    empires: Vec<EmpireStats>,
+}
+
+impl Summary {
+    fn to_csv(&self) -> String {
+        let mut csv = String::from("empire,energy,systems,fleet_strength\n");
+        for empire in self.empires.iter() {
+            csv += &format!(
+                "\"{}\",{},{},{}\n",
+                empire.name.replace('"', "\"\""),
+                empire.energy,
+                empire.systems,
+                empire.fleet_strength
+            );
+        }
+        csv
+    }
+}
+
+/// Skip the menu and start generating the galaxy.
+/// # Schedule
+/// Enter [AppState::InMenu].
+fn start(mut commands: Commands, args: Res<SimArgs>, mut app_state: ResMut<NextState<AppState>>) {
+    spawn_empires(
+        &mut commands,
+        args.params.seed,
+        (0..args.empires).map(|i| {
+            let setup = EmpireSetup {
+                name: format!("Empire {}", i + 1),
+                color: i % EMPIRE_COLORS.len(),
+                species: Species::ALL[i % Species::ALL.len()],
+            };
+            (None, setup)
+        }),
+    );
+    commands.spawn((Galaxy, PrimaryGalaxy, args.params.clone()));
+    app_state.set(AppState::Loading(LoadSource::Generation));
+}
+
+fn summarize(world: &mut World, args: &SimArgs) -> Summary {
+    let mut systems = HashMap::<Entity, usize>::new();
+    for owner in world
+        .query_filtered::<&Owner, With<PlanetarySystem>>()
+        .iter(world)
+    {
+        *systems.entry(owner.0.entity).or_default() += 1;
+    }
+
+    let mut fleets = HashMap::<Entity, usize>::new();
+    for owner in world.query_filtered::<&Owner, With<Fleet>>().iter(world) {
+        *fleets.entry(owner.0.entity).or_default() += 1;
+    }
+
+    let mut empires: Vec<_> = world
+        .query_filtered::<(Entity, &EmpireSetup, &Resources), With<Empire>>()
+        .iter(world)
+        .map(|(entity, setup, resources)| EmpireStats {
+            name: setup.name.clone(),
+            energy: resources.energy,
+            systems: systems.get(&entity).copied().unwrap_or_default(),
+            fleet_strength: fleets.get(&entity).copied().unwrap_or_default(),
+        })
+        .collect();
+    empires.sort_by(|a, b| a.name.cmp(&b.name));
+
+    Summary {
+        seed: args.params.seed,
+        size: args.params.size,
+        density: args.params.density,
+        ticks: world.resource::<GameTick>().0,
+        empires,
+    }
+}
+
+fn main() {
+    let args = match SimArgs::from_args(std::env::args()) {
+        Ok(args) => args,
+        Err(e) => {
+            eprintln!("error: {}\n{}", e, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(StatesPlugin)
+        .add_plugins(CorePlugin)
+        .add_plugins(GampMapGenPlugin)
+        .insert_resource(args.clone())
+        .add_systems(OnEnter(AppState::InMenu), start);
+    app.finish();
+    app.cleanup();
+
+    // generation runs in a task pool, keep updating until it is done.
+    let started = Instant::now();
+    while *app.world().resource::<State<AppState>>().get() != AppState::InGame {
+        if started.elapsed() > LOADING_TIMEOUT {
+            eprintln!("Map generation did not complete in {:?}.", LOADING_TIMEOUT);
+            std::process::exit(1);
+        }
+        app.update();
+    }
+
+    // run the ticks directly, without waiting for the fixed timestep.
+    while app.world().resource::<GameTick>().0 < args.ticks {
+        app.world_mut().run_schedule(FixedUpdate);
+    }
+
+    let summary = summarize(app.world_mut(), &args);
+    let text = match args.format {
+        Format::Json => {
+            serde_json::to_string_pretty(&summary).expect("Fail to serialize summary.") + "\n"
+        }
+        Format::Csv => summary.to_csv(),
+    };
+
+    let result = match args.output.as_ref() {
+        Some(path) => std::fs::write(path, text),
+        None => std::io::stdout().write_all(text.as_bytes()),
+    };
+    if let Err(e) = result {
+        eprintln!("Fail to write summary: {}", e);
+        std::process::exit(1);
+    }
+}
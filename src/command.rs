@@ -0,0 +1,90 @@
+//! Commands issued by players.
+//!
+//! Players never modify the game state directly. The UI pushes
+//! [GameCommand]s into [PendingCommands]; they are scheduled for a tick
+//! and executed from [TickCommands] during [crate::tick::TickSet::Simulation],
+//! in the same order on every peer of an online game.
+
+use crate::empire::{Owner, PlayerControlled};
+use crate::fleet::MoveOrder;
+use crate::game_map::planetary_system::PlanetarySystem;
+use crate::utils::{ObjectId, ObjectRef};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The id of a player in a game. `PlayerId(0)` is the host of an
+/// online game, or the only player of a local game.
+#[derive(
+    Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct PlayerId(pub u32);
+
+/// An action of a player that changes the game state.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GameCommand {
+    /// Order a fleet to move to a planetary system. Only the player
+    /// controlling the empire owning the fleet can issue it.
+    MoveFleet { fleet: ObjectId, target: ObjectId },
+    /// Hand an empire to a player. Only the host can issue it, when a
+    /// player joins a running game.
+    ControlEmpire { empire: ObjectId, player: PlayerId },
+}
+
+/// Commands issued locally that are not scheduled yet.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct PendingCommands(pub Vec<GameCommand>);
+
+/// Commands to be executed in the current tick, ordered by player.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct TickCommands(pub Vec<(PlayerId, GameCommand)>);
+
+/// Execute the commands of the current tick.
+/// # Schedule
+/// [FixedUpdate], in [crate::tick::TickSet::Simulation].
+pub fn apply(
+    mut commands: Commands,
+    tick_commands: Res<TickCommands>,
+    q_object: Query<(Entity, &ObjectId)>,
+    q_owner: Query<&Owner>,
+    q_controlled: Query<&PlayerControlled>,
+    q_system: Query<(), With<PlanetarySystem>>,
+) {
+    let find = |id: &ObjectId| {
+        q_object
+            .iter()
+            .find(|(_, object_id)| *object_id == id)
+            .map(|(entity, object_id)| ObjectRef::new(entity, *object_id))
+    };
+
+    // whether the player controls the empire owning the object.
+    let controls = |player: PlayerId, entity: Entity| {
+        q_owner
+            .get(entity)
+            .and_then(|owner| q_controlled.get(owner.0.entity))
+            .is_ok_and(|controlled| controlled.0 == player)
+    };
+
+    for (player, command) in tick_commands.0.iter() {
+        match command {
+            GameCommand::MoveFleet { fleet, target } => match (find(fleet), find(target)) {
+                (Some(fleet), Some(target))
+                    if controls(*player, fleet.entity) && q_system.contains(target.entity) =>
+                {
+                    commands.entity(fleet.entity).insert(MoveOrder { target });
+                }
+                _ => warn!("Ignore invalid command {:?} from {:?}", command, player),
+            },
+            GameCommand::ControlEmpire {
+                empire,
+                player: new,
+            } => match find(empire) {
+                Some(empire) if *player == PlayerId::default() => {
+                    commands
+                        .entity(empire.entity)
+                        .insert(PlayerControlled(*new));
+                }
+                _ => warn!("Ignore invalid command {:?} from {:?}", command, player),
+            },
+        }
+    }
+}
@@ -0,0 +1,63 @@
+//! The AI of empires without a player.
+//!
+//! The AI runs inside the simulation, so it must be deterministic: every
+//! decision is ordered by ids, never by query order.
+
+use super::{Empire, Owner, PlayerControlled, Unowned};
+use crate::fleet::{Fleet, MoveOrder};
+use crate::utils::{ObjectId, ObjectRef};
+use bevy::math::FloatOrd;
+use bevy::prelude::*;
+use bevy::utils::HashSet;
+
+type FleetData = (
+    Entity,
+    &'static ObjectId,
+    &'static Transform,
+    &'static Owner,
+    Option<&'static MoveOrder>,
+);
+
+/// Send idle fleets of AI empires to the nearest planetary system nobody
+/// owns, or heads to yet.
+/// # Schedule
+/// [FixedUpdate], in [crate::tick::TickSet::Simulation].
+pub fn explore(
+    mut commands: Commands,
+    q_empire: Query<(), (With<Empire>, Without<PlayerControlled>)>,
+    q_fleet: Query<FleetData, With<Fleet>>,
+    q_system: Query<(Entity, &ObjectId, &Transform), Unowned>,
+) {
+    let mut targeted: HashSet<Entity> = q_fleet
+        .iter()
+        .filter_map(|(.., order)| order.map(|order| order.target.entity))
+        .collect();
+
+    let mut idle: Vec<_> = q_fleet
+        .iter()
+        .filter(|(.., owner, order)| order.is_none() && q_empire.contains(owner.0.entity))
+        .collect();
+    if idle.is_empty() {
+        return;
+    }
+    idle.sort_by_key(|(_, id, ..)| id.0);
+
+    for (fleet, _, transform, ..) in idle {
+        let nearest = q_system
+            .iter()
+            .filter(|(system, ..)| !targeted.contains(system))
+            .min_by_key(|(_, id, system)| {
+                (
+                    FloatOrd(system.translation.distance(transform.translation)),
+                    id.0,
+                )
+            });
+
+        if let Some((system, id, _)) = nearest {
+            targeted.insert(system);
+            commands.entity(fleet).insert(MoveOrder {
+                target: ObjectRef::new(system, *id),
+            });
+        }
+    }
+}
@@ -0,0 +1,281 @@
+//! Empires, the factions competing in the galaxy.
+
+pub mod ai;
+
+use crate::command::PlayerId;
+use crate::fleet::{Fleet, LFleet, Vessels};
+use crate::game_map::galaxy::gen::GalaxyGenParams;
+use crate::game_map::galaxy::PrimaryGalaxy;
+use crate::game_map::planetary_system::PlanetarySystem;
+use crate::game_map::Coordinate;
+use crate::utils::{ObjectId, ObjectRef, RngExt};
+use bevy::color::palettes::tailwind;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use rand_pcg::Pcg64Mcg;
+use serde::{Deserialize, Serialize};
+
+/// The energy an owned planetary system yields per tick.
+pub const ENERGY_PER_SYSTEM: f64 = 0.1;
+
+/// A marker component for empires.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct Empire;
+
+/// The empire owning a game object.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct Owner(pub ObjectRef);
+
+/// The filter of planetary systems no empire owns yet.
+pub type Unowned = (With<PlanetarySystem>, Without<Owner>);
+
+/// The stockpile of an empire.
+#[derive(Component, Copy, Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Resources {
+    pub energy: f64,
+}
+
+/// The energy an empire gains per tick, by source.
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+pub struct Income {
+    /// the yield of the owned planetary systems.
+    pub systems: f64,
+    /// the share of the AI added or taken by the [Difficulty].
+    pub difficulty: f64,
+}
+
+impl Income {
+    /// The income of an empire owning `systems` planetary systems.
+    pub fn new(systems: usize, player: bool, difficulty: Difficulty) -> Self {
+        let base = systems as f64 * ENERGY_PER_SYSTEM;
+        let factor = if player { 1.0 } else { difficulty.ai_income() };
+        Self {
+            systems: base,
+            difficulty: base * (factor - 1.0),
+        }
+    }
+
+    pub fn total(&self) -> f64 {
+        self.systems + self.difficulty
+    }
+
+    /// The sources of the income, named for the player.
+    pub fn sources(&self) -> [(&'static str, f64); 2] {
+        [
+            ("Planetary Systems", self.systems),
+            ("Difficulty", self.difficulty),
+        ]
+    }
+}
+
+/// The player controlling an empire. Empires without it are
+/// controlled by the AI.
+#[derive(Component, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PlayerControlled(pub PlayerId);
+
+/// The species of the founders of an empire.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Species {
+    #[default]
+    Humanoid,
+    Mammalian,
+    Avian,
+    Reptilian,
+    Arthropoid,
+    Fungoid,
+    Lithoid,
+    Machine,
+}
+
+impl Species {
+    pub const ALL: [Species; 8] = [
+        Species::Humanoid,
+        Species::Mammalian,
+        Species::Avian,
+        Species::Reptilian,
+        Species::Arthropoid,
+        Species::Fungoid,
+        Species::Lithoid,
+        Species::Machine,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Species::Humanoid => "Humanoid",
+            Species::Mammalian => "Mammalian",
+            Species::Avian => "Avian",
+            Species::Reptilian => "Reptilian",
+            Species::Arthropoid => "Arthropoid",
+            Species::Fungoid => "Fungoid",
+            Species::Lithoid => "Lithoid",
+            Species::Machine => "Machine",
+        }
+    }
+
+    /// The next species in [Species::ALL], wrapping around.
+    pub fn next(&self) -> Self {
+        let i = Self::ALL.iter().position(|s| s == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+}
+
+/// How hard the game is, by the share of income the AI empires get.
+#[derive(Resource, Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// The factor on the income of empires controlled by the AI.
+    pub fn ai_income(&self) -> f64 {
+        match self {
+            Difficulty::Easy => 0.5,
+            Difficulty::Normal => 1.0,
+            Difficulty::Hard => 1.5,
+        }
+    }
+}
+
+/// The colours an empire can pick from.
+pub const EMPIRE_COLORS: [Srgba; 8] = [
+    tailwind::RED_500,
+    tailwind::ORANGE_500,
+    tailwind::YELLOW_400,
+    tailwind::GREEN_500,
+    tailwind::CYAN_500,
+    tailwind::BLUE_500,
+    tailwind::VIOLET_500,
+    tailwind::PINK_500,
+];
+
+/// The names of [EMPIRE_COLORS], in the same order.
+pub const EMPIRE_COLOR_NAMES: [&str; 8] = [
+    "Red", "Orange", "Yellow", "Green", "Cyan", "Blue", "Violet", "Pink",
+];
+
+/// The user choices for an empire before the game starts.
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct EmpireSetup {
+    pub name: String,
+    /// index into [EMPIRE_COLORS].
+    pub color: usize,
+    pub species: Species,
+}
+
+impl Default for EmpireSetup {
+    fn default() -> Self {
+        Self {
+            name: "United Nations of Earth".to_string(),
+            color: 5,
+            species: Species::default(),
+        }
+    }
+}
+
+impl EmpireSetup {
+    pub fn color(&self) -> Color {
+        EMPIRE_COLORS[self.color % EMPIRE_COLORS.len()].into()
+    }
+}
+
+/// Spawn the empires of a new game. Ids are drawn from the range of the
+/// seed reserved for empires, see [RngExt].
+pub fn spawn_empires(
+    commands: &mut Commands,
+    seed: u128,
+    empires: impl IntoIterator<Item = (Option<PlayerId>, EmpireSetup)>,
+) {
+    for (i, (player, setup)) in empires.into_iter().enumerate() {
+        let mut rng = Pcg64Mcg::new(seed);
+        rng.advance32(i);
+
+        let mut entity = commands.spawn((
+            Empire,
+            ObjectId::from_rng(&mut rng),
+            setup,
+            Resources::default(),
+        ));
+        if let Some(player) = player {
+            entity.insert(PlayerControlled(player));
+        }
+    }
+}
+
+/// Give every empire of a new game a home system and a starting fleet.
+/// Both are picked in the order of ids, so every peer agrees on them.
+/// # Schedule
+/// Enter [crate::AppState::InGame], unless a save is restored.
+pub fn settle(
+    mut commands: Commands,
+    q_galaxy: Query<(Entity, &ObjectId, &GalaxyGenParams), With<PrimaryGalaxy>>,
+    q_empire: Query<(Entity, &ObjectId), With<Empire>>,
+    q_system: Query<(Entity, &ObjectId, &Transform), Unowned>,
+) {
+    let Ok((galaxy, galaxy_id, params)) = q_galaxy.get_single() else {
+        return;
+    };
+    let galaxy = ObjectRef::new(galaxy, *galaxy_id);
+
+    let mut empires: Vec<_> = q_empire.iter().collect();
+    empires.sort_by_key(|(_, id)| id.0);
+    let mut systems: Vec<_> = q_system.iter().collect();
+    systems.sort_by_key(|(_, id, _)| id.0);
+
+    for (i, ((empire, empire_id), (system, _, transform))) in
+        empires.into_iter().zip(systems).enumerate()
+    {
+        let owner = Owner(ObjectRef::new(empire, *empire_id));
+        commands.entity(system).insert(owner);
+
+        // skip the numbers used for the ids of empires.
+        let mut rng = Pcg64Mcg::new(params.seed);
+        rng.advance32(i);
+        rng.advance16(1);
+        commands.spawn(LFleet {
+            marker: Fleet,
+            id: ObjectId::from_rng(&mut rng),
+            transform: Transform::from_translation(transform.translation),
+            vessels: Vessels(Vec::new()),
+            owner,
+            coordinate: Coordinate::Galaxy(galaxy),
+        });
+    }
+}
+
+/// Add the yield of owned planetary systems to their empire, scaled by
+/// the [Difficulty] for the AI.
+/// # Schedule
+/// [FixedUpdate], in [crate::tick::TickSet::Simulation].
+pub fn collect_income(
+    difficulty: Res<Difficulty>,
+    q_system: Query<&Owner, With<PlanetarySystem>>,
+    mut q_empire: Query<(Entity, &mut Resources, Has<PlayerControlled>), With<Empire>>,
+) {
+    let systems = count_systems(&q_system);
+    for (empire, mut resources, player) in q_empire.iter_mut() {
+        let count = systems.get(&empire).copied().unwrap_or(0);
+        resources.energy += Income::new(count, player, *difficulty).total();
+    }
+}
+
+/// The number of planetary systems owned by each empire.
+pub fn count_systems(q_system: &Query<&Owner, With<PlanetarySystem>>) -> HashMap<Entity, usize> {
+    let mut systems = HashMap::new();
+    for owner in q_system.iter() {
+        *systems.entry(owner.0.entity).or_default() += 1;
+    }
+    systems
+}
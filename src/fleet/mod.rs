@@ -1,13 +1,15 @@
+pub use crate::empire::Owner;
+
+use crate::empire::EmpireSetup;
 use crate::game_map::Coordinate;
 use crate::utils::*;
 use bevy::prelude::*;
+use bevy::utils::HashSet;
+use bevy_mod_picking::PickableBundle;
 
 #[derive(Component, Copy, Clone, Debug)]
 pub struct Fleet;
 
-#[derive(Component, Copy, Clone, Debug)]
-pub struct Owner(pub ObjectRef);
-
 #[derive(Component, Clone, Debug)]
 pub struct Vessels(pub Vec<ObjectRef>);
 
@@ -30,3 +32,89 @@ pub struct VFleet {
     pub inherited_visibility: InheritedVisibility,
     pub view_visibility: ViewVisibility,
 }
+
+/// Attach meshes to newly spawned fleets, in the color of their owner.
+/// The simulation does not need them, so this is only added along with
+/// the UI.
+/// # Schedule
+/// [Update]
+pub fn spawn_visuals(
+    mut commands: Commands,
+    asset: Res<AssetServer>,
+    q_fleet: Query<(Entity, &Owner), Added<Fleet>>,
+    q_empire: Query<&EmpireSetup>,
+) {
+    if q_fleet.is_empty() {
+        return;
+    }
+
+    let mesh = asset.add(Tetrahedron::default().mesh().build());
+    for (entity, owner) in q_fleet.iter() {
+        let color = q_empire
+            .get(owner.0.entity)
+            .map_or(Color::WHITE, |setup| setup.color());
+        commands.entity(entity).insert((
+            VFleet {
+                mesh: mesh.clone(),
+                material: asset.add(StandardMaterial {
+                    base_color: color,
+                    emissive: color.to_linear() * 4.0,
+                    ..default()
+                }),
+                transform: default(),
+                visibility: default(),
+                inherited_visibility: default(),
+                view_visibility: default(),
+            },
+            PickableBundle::default(),
+        ));
+    }
+}
+
+/// The destination of a moving fleet.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct MoveOrder {
+    pub target: ObjectRef,
+}
+
+/// The distance a fleet travels in one tick, in ly.
+pub const FLEET_SPEED: f32 = 0.5;
+
+/// Move fleets toward the target of their [MoveOrder]. A fleet arriving
+/// at a target without owner claims it for its empire.
+/// # Schedule
+/// [FixedUpdate], in [crate::tick::TickSet::Simulation].
+pub fn move_fleets(
+    mut commands: Commands,
+    mut q_fleet: Query<(Entity, &ObjectId, &mut Transform, &MoveOrder, &Owner), With<Fleet>>,
+    q_target: Query<(&Transform, Has<Owner>), Without<Fleet>>,
+) {
+    let mut arrived = Vec::new();
+
+    for (entity, id, mut transform, order, owner) in q_fleet.iter_mut() {
+        let Ok((target, owned)) = q_target.get(order.target.entity) else {
+            commands.entity(entity).remove::<MoveOrder>();
+            continue;
+        };
+
+        let offset = target.translation - transform.translation;
+        if offset.length() <= FLEET_SPEED {
+            transform.translation = target.translation;
+            commands.entity(entity).remove::<MoveOrder>();
+            if !owned {
+                arrived.push((*id, order.target.entity, *owner));
+            }
+        } else {
+            transform.translation += offset.normalize() * FLEET_SPEED;
+        }
+    }
+
+    // the first fleet by id wins, whatever order the query runs in.
+    arrived.sort_by_key(|(id, _, _)| id.0);
+    let mut claimed = HashSet::new();
+    for (_, target, owner) in arrived {
+        if claimed.insert(target) {
+            commands.entity(target).insert(owner);
+        }
+    }
+}
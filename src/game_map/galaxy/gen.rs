@@ -1,11 +1,11 @@
 use super::Galaxy;
-use crate::game_map::gen::GenState;
+use crate::game_map::gen::{GenState, LoadStage, LoadingProgress, TaskProgress};
 use crate::game_map::planetary_system::gen::PlnSysGenParams;
 use crate::game_map::planetary_system::PlanetarySystem;
 use crate::game_map::BoundingSize;
 use crate::utils::beta_params;
 use crate::utils::ObjectId;
-use crate::utils::{default_rng, RngExt};
+use crate::utils::{random_seed, RngExt};
 use bevy::math::FloatOrd;
 use bevy::prelude::*;
 use bevy::tasks::block_on;
@@ -16,34 +16,161 @@ use rand_distr::Beta;
 use rand_distr::Distribution;
 use rand_distr::Uniform;
 use rand_pcg::Pcg64Mcg;
+use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+
+/// The inner radius of [GalaxyShape::Ring], relative to the outer one.
+const RING_INNER: f32 = 0.5;
+/// The number of arms of [GalaxyShape::Spiral].
+const SPIRAL_ARMS: usize = 2;
+/// How far the arms of [GalaxyShape::Spiral] wind, in radians from the
+/// core to the rim.
+const SPIRAL_TWIST: f32 = 1.5 * std::f32::consts::TAU;
+/// The angular width of an arm of [GalaxyShape::Spiral], in radians.
+const SPIRAL_WIDTH: f32 = 1.2;
+
+/// The highest density where every planetary system of a galaxy finds a
+/// place, as they keep apart from each other.
+const MAX_DENSITY: f32 = 0.125;
+
+/// The layout of the planetary systems, seen from above.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GalaxyShape {
+    /// spread evenly over a square.
+    #[default]
+    Square,
+    Disc,
+    /// a disc with an empty core.
+    Ring,
+    /// arms winding around the core.
+    Spiral,
+}
+
+impl GalaxyShape {
+    pub const ALL: [GalaxyShape; 4] = [
+        GalaxyShape::Square,
+        GalaxyShape::Disc,
+        GalaxyShape::Ring,
+        GalaxyShape::Spiral,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GalaxyShape::Square => "Square",
+            GalaxyShape::Disc => "Disc",
+            GalaxyShape::Ring => "Ring",
+            GalaxyShape::Spiral => "Spiral",
+        }
+    }
+
+    /// The area of the shape with a radius of 1.
+    fn area(&self) -> f32 {
+        use std::f32::consts::PI;
+        match self {
+            GalaxyShape::Square => 4.0,
+            GalaxyShape::Disc | GalaxyShape::Spiral => PI,
+            GalaxyShape::Ring => PI * (1.0 - RING_INNER * RING_INNER),
+        }
+    }
+
+    /// How much denser than average the densest parts of the shape are.
+    fn crowding(&self) -> f32 {
+        match self {
+            GalaxyShape::Spiral => 2.0,
+            _ => 1.0,
+        }
+    }
+
+    /// Move a position drawn evenly in the square of half size `radius`
+    /// into the shape, keeping it evenly spread.
+    fn place(&self, square: Vec2, radius: f32) -> Vec2 {
+        use std::f32::consts::TAU;
+        // two independent numbers in 0..=1.
+        let (u, v) = (
+            0.5 * (square.x / radius + 1.0),
+            0.5 * (square.y / radius + 1.0),
+        );
+        let (r, angle) = match self {
+            GalaxyShape::Square => return square,
+            GalaxyShape::Disc => (u.sqrt(), v * TAU),
+            GalaxyShape::Ring => {
+                let inner = RING_INNER * RING_INNER;
+                ((inner + (1.0 - inner) * u).sqrt(), v * TAU)
+            }
+            GalaxyShape::Spiral => {
+                let arm = (v * SPIRAL_ARMS as f32)
+                    .floor()
+                    .min(SPIRAL_ARMS as f32 - 1.0);
+                let across = v * SPIRAL_ARMS as f32 - arm - 0.5;
+                let r = u.sqrt();
+                let angle =
+                    arm * TAU / SPIRAL_ARMS as f32 + r * SPIRAL_TWIST + across * SPIRAL_WIDTH;
+                (r, angle)
+            }
+        };
+        Vec2::from_angle(angle) * r * radius
+    }
+
+    /// The draws of positions per planetary system, before giving up on
+    /// placing the remaining ones.
+    fn attempts(&self) -> usize {
+        match self {
+            GalaxyShape::Square => 2,
+            _ => 4,
+        }
+    }
+}
 
 /// Used for game map generation. This is the initial parameters for
 /// the galaxy random generation.
-#[derive(Component, Clone, Debug)]
+#[derive(Component, Clone, Debug, Serialize, Deserialize)]
 pub struct GalaxyGenParams {
-    /// the random generator for this galaxy.
-    pub rng: Pcg64Mcg,
+    /// the seed of the random generator for this galaxy.
+    pub seed: u128,
     /// the number of planetary system in the galaxy
     pub size: usize,
     /// the density of the planetary system, in terms of 1/ly^2
     pub density: f32,
+    /// missing from older saves, which were all square.
+    #[serde(default)]
+    pub shape: GalaxyShape,
     // TODO: add star stages and nebula
 }
 
 impl Default for GalaxyGenParams {
     fn default() -> Self {
         Self {
-            rng: default_rng(),
+            seed: random_seed(),
             size: 8192,
             density: 0.0625,
+            shape: GalaxyShape::default(),
         }
     }
 }
 
 impl GalaxyGenParams {
+    /// the supported number of planetary systems.
+    pub const SIZE_RANGE: RangeInclusive<usize> = 256..=32768;
+    /// the supported densities of planetary systems.
+    pub const DENSITY_RANGE: RangeInclusive<f32> = 0.015625..=MAX_DENSITY;
+
+    /// the random generator for this galaxy.
+    pub fn rng(&self) -> Pcg64Mcg {
+        let mut rng = Pcg64Mcg::new(self.seed);
+        // the first 2^64 numbers are reserved for empires.
+        rng.advance64(1);
+        rng
+    }
+
+    /// Whether every planetary system finds a place at this density.
+    pub fn can_fill(&self) -> bool {
+        self.density * self.shape.crowding() <= MAX_DENSITY
+    }
+
     fn radius(&self) -> f32 {
-        0.5 * (self.size as f32 / self.density).sqrt()
+        // the shape holds `size` systems at `density`.
+        (self.size as f32 / self.density / self.shape.area()).sqrt()
     }
 
     fn height(&self) -> f32 {
@@ -56,42 +183,62 @@ impl GalaxyGenParams {
 }
 
 #[derive(Component, Debug)]
-pub struct GenTask(Task<Vec<PlnSysGenParams>>);
+pub struct GenTask {
+    task: Task<Vec<PlnSysGenParams>>,
+    progress: TaskProgress,
+}
+
+impl GenTask {
+    /// Stop the task at its next step. Dropping the task alone would let
+    /// it run to the end.
+    pub fn cancel(&self) {
+        self.progress.cancel();
+    }
+}
+
+/// The galaxies waiting for generation. Generated ones keep their params,
+/// as they are part of saves, but already have a [BoundingSize].
+type Ungenerated = (With<Galaxy>, Without<GenTask>, Without<BoundingSize>);
 
 pub fn init_galaxy_gen_task(
     mut commands: Commands,
-    q_galaxy: Query<(Entity, &GalaxyGenParams), With<Galaxy>>,
+    q_galaxy: Query<(Entity, &GalaxyGenParams), Ungenerated>,
 ) {
     let task_pool = AsyncComputeTaskPool::get();
 
-    let (entity, params) = q_galaxy.single();
-    let params = params.clone();
-    let rng = params.rng.clone();
+    for (entity, params) in q_galaxy.iter() {
+        let params = params.clone();
+        let rng = params.rng();
+        let progress = TaskProgress::default();
 
-    let task = task_pool.spawn(async move { new_planetary_systems(params, rng) });
+        let reporter = progress.clone();
+        let task = task_pool.spawn(async move { new_planetary_systems(params, rng, reporter) });
 
-    commands.entity(entity).insert(GenTask(task));
+        commands.entity(entity).insert(GenTask { task, progress });
+    }
 }
 
 pub fn handle_galaxy_gen_task(
     mut commands: Commands,
     mut gen_state: ResMut<NextState<GenState>>,
+    mut progress: ResMut<LoadingProgress>,
     mut q_galaxy: Query<(Entity, &mut GenTask, &GalaxyGenParams), With<Galaxy>>,
 ) {
     let mut count = 0;
 
     for (entity, mut task, params) in q_galaxy.iter_mut() {
         count += 1;
+        progress.set_if_neq(task.progress.get());
 
-        if let Some(planetary_systems) = block_on(future::poll_once(&mut task.0)) {
+        if let Some(planetary_systems) = block_on(future::poll_once(&mut task.task)) {
             let xyz = Vec3::new(params.radius(), params.radius(), params.height());
 
+            // the params are kept on the galaxy, they are part of saves.
             commands
                 .entity(entity)
-                .remove::<GalaxyGenParams>()
                 .remove::<GenTask>()
                 .insert(BoundingSize::new(xyz))
-                .insert(ObjectId::default());
+                .insert(ObjectId::from_rng(&mut params.rng()));
 
             for params in planetary_systems {
                 commands.spawn((PlanetarySystem, params));
@@ -104,7 +251,14 @@ pub fn handle_galaxy_gen_task(
     }
 }
 
-fn new_planetary_systems(galaxy: GalaxyGenParams, mut rng: Pcg64Mcg) -> Vec<PlnSysGenParams> {
+/// The planetary systems placed between two reports of progress.
+const REPORT_INTERVAL: usize = 256;
+
+fn new_planetary_systems(
+    galaxy: GalaxyGenParams,
+    mut rng: Pcg64Mcg,
+    progress: TaskProgress,
+) -> Vec<PlnSysGenParams> {
     let mut planetary_systems = Vec::with_capacity(galaxy.size);
 
     // assign positions
@@ -120,12 +274,17 @@ fn new_planetary_systems(galaxy: GalaxyGenParams, mut rng: Pcg64Mcg) -> Vec<PlnS
                 .map(|x| (x - 0.5) * 2.0 * galaxy.height())
         };
 
-        // double the number of iterations to compensate for potential
+        // draw more positions than systems to compensate for potential
         // rejected position.
-        'outer: for _ in 0..(galaxy.size * 2) {
+        'outer: for _ in 0..(galaxy.size * galaxy.shape.attempts()) {
+            if progress.is_cancelled() {
+                return Vec::new();
+            }
             let x = radius_distr.sample(&mut rng);
             let y = radius_distr.sample(&mut rng);
             let z = height_distr.sample(&mut rng);
+            let xy = galaxy.shape.place(Vec2::new(x, y), galaxy.radius());
+            let (x, y) = (xy.x, xy.y);
             let candidate = Vec3::new(x, y, z);
 
             // reject this value if it is to close to a existing one.
@@ -140,15 +299,24 @@ fn new_planetary_systems(galaxy: GalaxyGenParams, mut rng: Pcg64Mcg) -> Vec<PlnS
             }
 
             x_range.insert(FloatOrd(x), candidate);
+            if x_range.len().is_multiple_of(REPORT_INTERVAL) {
+                let placed = x_range.len() as f32 / galaxy.size as f32;
+                progress.report(LoadStage::Positions, placed);
+            }
 
             if x_range.len() == galaxy.size as usize {
                 break 'outer;
             }
         }
 
+        if progress.is_cancelled() {
+            return Vec::new();
+        }
+        progress.report(LoadStage::Systems, 0.0);
         x_range.values().enumerate().for_each(|(i, position)| {
-            let mut rng = galaxy.rng.clone();
-            rng.advance32(i);
+            // the first 2^32 numbers belong to the galaxy itself.
+            let mut rng = galaxy.rng();
+            rng.advance32(i + 1);
             planetary_systems.push(PlnSysGenParams {
                 rng,
                 position: *position,
@@ -1,49 +1,141 @@
-use super::galaxy::gen::{handle_galaxy_gen_task, init_galaxy_gen_task};
-use super::galaxy::Galaxy;
+mod progress;
+
+pub use progress::{LoadStage, LoadingAssets, LoadingProgress, TaskProgress};
+
+use super::galaxy::gen::{handle_galaxy_gen_task, init_galaxy_gen_task, GenTask};
+use super::galaxy::{Galaxy, PrimaryGalaxy};
 use super::planetary_system::gen::spawn_planetary_systems;
-use super::{BoundingSize, Coordinate};
+use super::planetary_system::PlanetarySystem;
+use super::{BoundingSize, MapReady};
+use crate::empire::Empire;
+use crate::fleet::Fleet;
+use crate::save::SaveGame;
 use crate::states::AppStateLoading;
-use crate::ui::camera::PrimCamFreeMotion;
 use crate::utils::{ObjectId, ObjectRef};
-use crate::{states::LoadSource, ui::PrimaryCamera, AppState};
+use crate::{states::LoadSource, AppState};
 use bevy::prelude::*;
 
 #[derive(SubStates, Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
-#[source(AppState = AppState::Loading(LoadSource::Generation))]
+#[source(AppState = AppState::Loading(LoadSource::Generation | LoadSource::FromLocal | LoadSource::FromOnline))]
 pub enum GenState {
     #[default]
     InitGalaxy,
     InitPlnSys,
+    LoadAssets,
 }
 
 /// The plugin for game map generation.
 pub struct GampMapGenPlugin;
 
-pub fn setup_primary_camera(
-    q_galaxy: Query<(Entity, &ObjectId, &BoundingSize), With<Galaxy>>,
-    q_camera: Query<Entity, With<PrimaryCamera>>,
+/// Sent when the loading is abandoned, along with leaving
+/// [AppState::Loading] for the menu.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct CancelLoading;
+
+/// The objects of the game being loaded.
+type Loaded = Or<(
+    With<Galaxy>,
+    With<PlanetarySystem>,
+    With<Empire>,
+    With<Fleet>,
+)>;
+
+/// Stop the generation tasks and despawn what is loaded so far.
+/// # Schedule
+/// [Update], on [CancelLoading].
+pub fn cancel_loading(
     mut commands: Commands,
+    q_task: Query<&GenTask>,
+    q_loaded: Query<Entity, Loaded>,
+) {
+    for task in q_task.iter() {
+        task.cancel();
+    }
+    for entity in q_loaded.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    commands.remove_resource::<SaveGame>();
+    info!("Loading cancelled.");
+}
+
+/// Announce the primary galaxy once loading is completed.
+/// # Schedule
+/// Enter [AppState::InGame].
+pub fn announce_map_ready(
+    q_galaxy: Query<(Entity, &ObjectId, &BoundingSize), With<PrimaryGalaxy>>,
+    mut ew_map_ready: EventWriter<MapReady>,
 ) {
-    let camera = q_camera.single();
-
-    for (entity, object_id, size) in q_galaxy.iter() {
-        commands
-            .entity(camera)
-            .insert(Coordinate::Galaxy(ObjectRef::new(entity, *object_id)))
-            .insert(PrimCamFreeMotion { half_size: size.half_size });
-        break;
+    match q_galaxy.get_single() {
+        Ok((entity, object_id, size)) => {
+            ew_map_ready.send(MapReady {
+                galaxy: ObjectRef::new(entity, *object_id),
+                size: *size,
+            });
+        }
+        Err(e) => error!("Fail to find the primary galaxy: {}", e),
     }
 }
 
 impl Plugin for GampMapGenPlugin {
     fn build(&self, app: &mut App) {
         app.add_sub_state::<GenState>()
+            .init_resource::<LoadingProgress>()
+            .init_resource::<LoadingAssets>()
+            .add_systems(OnEnter(AppStateLoading), progress::reset)
             .add_systems(OnEnter(GenState::InitGalaxy), init_galaxy_gen_task)
             .add_systems(
                 Update,
                 handle_galaxy_gen_task.run_if(in_state(GenState::InitGalaxy)),
             )
             .add_systems(OnEnter(GenState::InitPlnSys), spawn_planetary_systems)
-            .add_systems(OnExit(AppStateLoading), setup_primary_camera);
+            .add_systems(
+                Update,
+                progress::wait_for_assets.run_if(in_state(GenState::LoadAssets)),
+            )
+            .add_event::<CancelLoading>()
+            .add_systems(Update, cancel_loading.run_if(on_event::<CancelLoading>()))
+            .add_event::<MapReady>()
+            .add_systems(OnEnter(AppState::InGame), announce_map_ready);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_map::galaxy::gen::GalaxyGenParams;
+    use bevy::state::app::StatesPlugin;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn send_map_ready_once_generated() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .init_state::<AppState>()
+            .add_computed_state::<AppStateLoading>()
+            .add_plugins(GampMapGenPlugin);
+
+        let params = GalaxyGenParams {
+            size: *GalaxyGenParams::SIZE_RANGE.start(),
+            ..default()
+        };
+        let galaxy = app.world_mut().spawn((Galaxy, PrimaryGalaxy, params)).id();
+        app.world_mut()
+            .resource_mut::<NextState<AppState>>()
+            .set(AppState::Loading(LoadSource::Generation));
+
+        // generation runs in a task pool, keep updating until it is done.
+        let started = Instant::now();
+        while *app.world().resource::<State<AppState>>().get() != AppState::InGame {
+            assert!(started.elapsed() < Duration::from_secs(60));
+            app.update();
+        }
+
+        let events = app.world().resource::<Events<MapReady>>();
+        let mut reader = events.get_reader();
+        let map_ready: Vec<_> = reader.read(events).collect();
+        assert_eq!(map_ready.len(), 1);
+        let size = app.world().get::<BoundingSize>(galaxy).unwrap();
+        assert_eq!(map_ready[0].galaxy.entity, galaxy);
+        assert_eq!(map_ready[0].size.half_size, size.half_size);
     }
 }
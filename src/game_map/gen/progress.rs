@@ -0,0 +1,144 @@
+//! The progress of loading a game, shown by the loading screen.
+//!
+//! Each stage reports how far it is into [LoadingProgress]. Stages running
+//! in an async task report through a [TaskProgress] shared with the task,
+//! which also tells the task when the loading is cancelled.
+
+use crate::AppState;
+use bevy::asset::LoadState;
+use bevy::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// The stages of loading a game, in order.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LoadStage {
+    /// place the planetary systems in the galaxy.
+    #[default]
+    Positions,
+    /// spawn the planetary systems.
+    Systems,
+    /// wait for the [LoadingAssets].
+    Assets,
+}
+
+impl LoadStage {
+    pub const ALL: [LoadStage; 3] = [LoadStage::Positions, LoadStage::Systems, LoadStage::Assets];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            LoadStage::Positions => "Charting the stars",
+            LoadStage::Systems => "Forming planetary systems",
+            LoadStage::Assets => "Loading assets",
+        }
+    }
+}
+
+/// How far the loading is.
+#[derive(Resource, Copy, Clone, Default, Debug, PartialEq)]
+pub struct LoadingProgress {
+    pub stage: LoadStage,
+    /// the progress of the current stage, from 0 to 1.
+    pub progress: f32,
+}
+
+impl LoadingProgress {
+    pub fn new(stage: LoadStage, progress: f32) -> Self {
+        Self {
+            stage,
+            progress: progress.clamp(0.0, 1.0),
+        }
+    }
+
+    /// The progress of the whole loading, from 0 to 1. Every stage counts
+    /// the same.
+    pub fn total(&self) -> f32 {
+        let done = LoadStage::ALL
+            .iter()
+            .position(|stage| *stage == self.stage)
+            .unwrap_or(0);
+        (done as f32 + self.progress) / LoadStage::ALL.len() as f32
+    }
+}
+
+/// The [LoadingProgress] of an async task, shared with the task.
+#[derive(Clone, Default, Debug)]
+pub struct TaskProgress {
+    progress: Arc<Mutex<LoadingProgress>>,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl TaskProgress {
+    /// Report the progress, from the task.
+    pub fn report(&self, stage: LoadStage, progress: f32) {
+        if let Ok(mut shared) = self.progress.lock() {
+            *shared = LoadingProgress::new(stage, progress);
+        }
+    }
+
+    /// The last progress reported by the task.
+    pub fn get(&self) -> LoadingProgress {
+        self.progress
+            .lock()
+            .map(|shared| *shared)
+            .unwrap_or_default()
+    }
+
+    /// Ask the task to stop.
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether the task should stop, checked by the task between steps.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+}
+
+/// The assets the game waits for before it starts, kept loaded until the
+/// next loading.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct LoadingAssets(pub Vec<UntypedHandle>);
+
+/// Start the progress and the assets of a new loading over.
+/// # Schedule
+/// Enter [crate::states::AppStateLoading].
+pub fn reset(mut progress: ResMut<LoadingProgress>, mut assets: ResMut<LoadingAssets>) {
+    *progress = LoadingProgress::default();
+    assets.0.clear();
+}
+
+/// Start the game once the [LoadingAssets] are loaded. Assets that fail to
+/// load are not waited for.
+/// # Schedule
+/// [Update], in [super::GenState::LoadAssets].
+pub fn wait_for_assets(
+    assets: Res<LoadingAssets>,
+    asset_server: Option<Res<AssetServer>>,
+    mut progress: ResMut<LoadingProgress>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    // without an asset server, as in headless runs, there is nothing to load.
+    let done = match asset_server {
+        Some(asset_server) => assets
+            .0
+            .iter()
+            .filter(|handle| {
+                matches!(
+                    asset_server.load_state(handle.id()),
+                    LoadState::Loaded | LoadState::Failed(_)
+                )
+            })
+            .count(),
+        None => assets.0.len(),
+    };
+    let total = assets.0.len().max(1);
+    progress.set_if_neq(LoadingProgress::new(
+        LoadStage::Assets,
+        done as f32 / total as f32,
+    ));
+
+    if done == assets.0.len() {
+        app_state.set(AppState::InGame);
+    }
+}
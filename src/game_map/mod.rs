@@ -5,7 +5,10 @@ pub mod planetary_system;
 use std::ops::Deref;
 
 use crate::utils::ObjectRef;
-use bevy::{math::Vec3, prelude::Component};
+use bevy::{
+    math::Vec3,
+    prelude::{Component, Event},
+};
 
 /// Determine which coordinate system the entity is using. If it's None, then
 /// a global coordinate system is used; otherwise, the coordinate system of
@@ -37,3 +40,12 @@ impl BoundingSize {
         Self { half_size }
     }
 }
+
+/// Sent once the game map is generated or loaded, for anything that needs
+/// to set up around it, such as the camera.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct MapReady {
+    /// the primary galaxy.
+    pub galaxy: ObjectRef,
+    pub size: BoundingSize,
+}
@@ -1,14 +1,7 @@
-use super::PlanetarySystem;
-use crate::{
-    game_map::{
-        galaxy::Galaxy,
-        planetary_system::{PlanetarySystemBundle, VPlanetarySystemBundle},
-        BoundingSize,
-    },
-    states::AppState,
-};
+use super::{PlanetarySystem, Star};
+use crate::game_map::gen::{GenState, LoadStage, LoadingProgress};
+use crate::{game_map::planetary_system::PlanetarySystemBundle, utils::ObjectId};
 use bevy::prelude::*;
-use bevy_mod_picking::PickableBundle;
 use rand_pcg::Pcg64Mcg;
 
 #[derive(Component, Clone, Debug)]
@@ -21,44 +14,24 @@ pub struct PlnSysGenParams {
 
 pub fn spawn_planetary_systems(
     mut commands: Commands,
-    asset: Res<AssetServer>,
-    q_pln_sys: Query<&PlnSysGenParams, With<PlanetarySystem>>,
-    mut app_state: ResMut<NextState<AppState>>,
+    q_pln_sys: Query<(Entity, &PlnSysGenParams), With<PlanetarySystem>>,
+    mut progress: ResMut<LoadingProgress>,
+    mut gen_state: ResMut<NextState<GenState>>,
 ) {
-    let mesh = asset.add(Sphere::default().mesh().ico(16).unwrap());
-
-    for planetary_system in q_pln_sys.iter() {
-        // let r = (planetary_system.mass - min_mass) / (max_mass - min_mass);
-        // let b = 1.0 - r;
-
-        let material = asset.add(StandardMaterial {
-            base_color: LinearRgba::new(1000.0, 1000.0, 2000.0, 1.0).into(),
+    for (entity, planetary_system) in q_pln_sys.iter() {
+        // the params are only placeholders until the system is spawned.
+        commands.entity(entity).despawn();
+        commands.spawn(PlanetarySystemBundle {
+            id: ObjectId::from_rng(&mut planetary_system.rng.clone()),
+            transform: Transform::from_translation(planetary_system.position)
+                .with_scale(Vec3::ONE * planetary_system.mass * 0.2),
+            star: Star {
+                mass: planetary_system.mass,
+            },
             ..default()
         });
-
-        commands.spawn((
-            PlanetarySystemBundle {
-                transform: Transform::from_translation(planetary_system.position)
-                    .with_scale(Vec3::ONE * planetary_system.mass * 0.2),
-                ..default()
-            },
-            VPlanetarySystemBundle {
-                mesh: mesh.clone(),
-                material: material.clone(),
-                ..default()
-            },
-            PickableBundle::default(),
-        ));
     }
 
-    commands.spawn(PbrBundle {
-        mesh: asset.add(Cuboid::new(10.0, 10.0, 0.1).mesh().build()),
-        material: asset.add(StandardMaterial {
-            base_color: LinearRgba::new(1.0, 1.0, 1.0, 1.0).into(),
-            ..default()
-        }),
-        ..default()
-    });
-
-    app_state.set(AppState::InGame);
+    *progress = LoadingProgress::new(LoadStage::Systems, 1.0);
+    gen_state.set(GenState::LoadAssets);
 }
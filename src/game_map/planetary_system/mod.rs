@@ -2,6 +2,7 @@ pub mod gen;
 
 use crate::utils::{ObjectId, ObjectRef};
 use bevy::prelude::*;
+use bevy_mod_picking::PickableBundle;
 
 #[derive(Component, Copy, Clone, Default, Debug)]
 pub struct PlanetarySystem;
@@ -9,12 +10,41 @@ pub struct PlanetarySystem;
 #[derive(Component, Clone, Default, Debug)]
 pub struct Planets(pub Vec<ObjectRef>);
 
+/// The star of a planetary system.
+#[derive(Component, Copy, Clone, Debug, PartialEq)]
+pub struct Star {
+    /// in solar masses.
+    pub mass: f32,
+}
+
+impl Default for Star {
+    fn default() -> Self {
+        Self { mass: 1.0 }
+    }
+}
+
+impl Star {
+    /// The spectral class of a main sequence star of this mass.
+    pub fn class(&self) -> &'static str {
+        match self.mass {
+            m if m < 0.45 => "M",
+            m if m < 0.8 => "K",
+            m if m < 1.04 => "G",
+            m if m < 1.4 => "F",
+            m if m < 2.1 => "A",
+            m if m < 16.0 => "B",
+            _ => "O",
+        }
+    }
+}
+
 #[derive(Clone, Default, Debug, Bundle)]
 pub struct PlanetarySystemBundle {
     pub marker: PlanetarySystem,
     pub id: ObjectId,
     pub transform: Transform,
     pub planets: Planets,
+    pub star: Star,
 }
 
 #[derive(Clone, Default, Debug, Bundle)]
@@ -26,3 +56,34 @@ pub struct VPlanetarySystemBundle {
     pub inherited_visibility: InheritedVisibility,
     pub view_visibility: ViewVisibility,
 }
+
+/// Attach meshes to newly spawned planetary systems. The simulation does
+/// not need them, so this is only added along with the UI.
+/// # Schedule
+/// [Update]
+pub fn spawn_visuals(
+    mut commands: Commands,
+    asset: Res<AssetServer>,
+    q_pln_sys: Query<Entity, (Added<PlanetarySystem>, With<ObjectId>)>,
+) {
+    if q_pln_sys.is_empty() {
+        return;
+    }
+
+    let mesh = asset.add(Sphere::default().mesh().ico(16).unwrap());
+    let material = asset.add(StandardMaterial {
+        base_color: LinearRgba::new(1000.0, 1000.0, 2000.0, 1.0).into(),
+        ..default()
+    });
+
+    for entity in q_pln_sys.iter() {
+        commands.entity(entity).insert((
+            VPlanetarySystemBundle {
+                mesh: mesh.clone(),
+                material: material.clone(),
+                ..default()
+            },
+            PickableBundle::default(),
+        ));
+    }
+}
@@ -1,13 +1,21 @@
 #![allow(mixed_script_confusables)]
 
+pub mod command;
+pub mod empire;
 pub mod fleet;
 pub mod game_map;
+pub mod net;
+pub mod save;
 pub mod states;
+pub mod tick;
 pub mod ui;
 pub mod utils;
 
 use bevy::prelude::*;
+use command::{PendingCommands, TickCommands};
+use net::NetSession;
 use states::{complete_setup, AppState, AppStateLoading};
+use tick::{GameSpeed, GameTick, TickSet, TickStep, TICKS_PER_SECOND};
 
 pub struct CorePlugin;
 
@@ -17,5 +25,56 @@ impl Plugin for CorePlugin {
             .enable_state_scoped_entities::<AppState>()
             .add_computed_state::<AppStateLoading>()
             .add_systems(PostStartup, complete_setup);
+
+        app.insert_resource(Time::<Fixed>::from_hz(TICKS_PER_SECOND))
+            .init_resource::<GameTick>()
+            .init_resource::<GameSpeed>()
+            .init_resource::<empire::Difficulty>()
+            .init_resource::<save::PlayTime>()
+            .init_resource::<TickStep>()
+            .init_resource::<PendingCommands>()
+            .init_resource::<TickCommands>()
+            .configure_sets(
+                FixedUpdate,
+                (TickSet::Sync, TickSet::Simulation, TickSet::Advance)
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .configure_sets(FixedUpdate, TickSet::Simulation.run_if(tick::tick_ready))
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (
+                    (tick::reset, tick::reset_speed),
+                    (empire::settle, save::reset_play_time)
+                        .run_if(not(resource_exists::<save::SaveGame>)),
+                    save::restore.run_if(resource_exists::<save::SaveGame>),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    tick::local_sync
+                        .in_set(TickSet::Sync)
+                        .run_if(not(resource_exists::<NetSession>)),
+                    (
+                        command::apply,
+                        empire::ai::explore,
+                        fleet::move_fleets,
+                        empire::collect_income,
+                    )
+                        .chain()
+                        .in_set(TickSet::Simulation),
+                    tick::advance.in_set(TickSet::Advance),
+                ),
+            )
+            .add_systems(OnExit(AppState::InGame), tick::reset_speed)
+            .add_systems(
+                Update,
+                (
+                    save::count_play_time.run_if(in_state(AppState::InGame)),
+                    tick::apply_speed.run_if(resource_changed::<GameSpeed>),
+                ),
+            );
     }
 }
@@ -2,6 +2,7 @@ use bevy::prelude::*;
 use bevy_mod_picking::{low_latency_window_plugin, picking_core, DefaultPickingPlugins};
 use sickle_ui::SickleUiPlugin;
 use stellaris::game_map::gen::GampMapGenPlugin;
+use stellaris::net::{NetLaunch, NetPlugin};
 use stellaris::ui::*;
 use stellaris::*;
 
@@ -18,7 +19,12 @@ fn main() {
     // internal plugins
     app.add_plugins(CorePlugin)
         .add_plugins(UserInterfacePlugin)
-        .add_plugins(GampMapGenPlugin);
+        .add_plugins(GampMapGenPlugin)
+        .add_plugins(NetPlugin);
+
+    if let Some(launch) = NetLaunch::from_args(std::env::args()) {
+        app.insert_resource(launch);
+    }
 
     app.run();
 }
@@ -0,0 +1,69 @@
+//! A non-blocking, message-oriented wrapper of [TcpStream].
+
+use super::protocol::{decode, encode, NetMessage};
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+
+pub struct Connection {
+    stream: TcpStream,
+    incoming: Vec<u8>,
+    outgoing: Vec<u8>,
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+        })
+    }
+
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.stream.peer_addr()
+    }
+
+    /// Queue a message, it is written on the next [Connection::flush].
+    pub fn send(&mut self, message: &NetMessage) {
+        encode(message, &mut self.outgoing);
+    }
+
+    /// Write as much queued data as the socket accepts.
+    pub fn flush(&mut self) -> io::Result<()> {
+        while !self.outgoing.is_empty() {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => return Err(io::ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    self.outgoing.drain(..n);
+                }
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Read all available data and return the complete messages. An error
+    /// means the connection is lost.
+    pub fn receive(&mut self) -> io::Result<Vec<NetMessage>> {
+        let mut chunk = [0u8; 4096];
+        loop {
+            match self.stream.read(&mut chunk) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => self.incoming.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        let mut messages = Vec::new();
+        while let Some(message) = decode(&mut self.incoming)? {
+            messages.push(message);
+        }
+        Ok(messages)
+    }
+}
@@ -0,0 +1,126 @@
+//! The lobby of an online game, where players gather before it starts.
+//!
+//! The host owns the [Lobby]: clients send their changes to the host, and
+//! the host broadcasts the whole lobby to everyone whenever it changes.
+
+use super::protocol::NetMessage;
+use super::{NetEvent, NetSession};
+use crate::command::PlayerId;
+use crate::empire::{EmpireSetup, EMPIRE_COLORS};
+use crate::game_map::galaxy::gen::GalaxyGenParams;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A player in the lobby.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LobbySlot {
+    pub player: PlayerId,
+    pub empire: EmpireSetup,
+    pub ready: bool,
+}
+
+/// Everything needed to start an online game.
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct Lobby {
+    pub slots: Vec<LobbySlot>,
+    pub params: GalaxyGenParams,
+}
+
+impl Lobby {
+    pub fn new(host: PlayerId) -> Self {
+        let mut lobby = Self {
+            slots: Vec::new(),
+            params: GalaxyGenParams::default(),
+        };
+        lobby.join(host);
+        lobby
+    }
+
+    pub fn players(&self) -> Vec<PlayerId> {
+        self.slots.iter().map(|slot| slot.player).collect()
+    }
+
+    pub fn slot(&self, player: PlayerId) -> Option<&LobbySlot> {
+        self.slots.iter().find(|slot| slot.player == player)
+    }
+
+    pub fn slot_mut(&mut self, player: PlayerId) -> Option<&mut LobbySlot> {
+        self.slots.iter_mut().find(|slot| slot.player == player)
+    }
+
+    /// Add a slot for the player, with a colour nobody uses yet.
+    pub fn join(&mut self, player: PlayerId) {
+        if self.slot(player).is_some() {
+            return;
+        }
+        let color = (0..EMPIRE_COLORS.len())
+            .find(|i| self.slots.iter().all(|slot| slot.empire.color != *i))
+            .unwrap_or(0);
+        self.slots.push(LobbySlot {
+            player,
+            empire: EmpireSetup {
+                name: format!("Empire {}", self.slots.len() + 1),
+                color,
+                ..default()
+            },
+            ready: false,
+        });
+    }
+
+    pub fn leave(&mut self, player: PlayerId) {
+        self.slots.retain(|slot| slot.player != player);
+    }
+
+    pub fn all_ready(&self) -> bool {
+        self.slots.iter().all(|slot| slot.ready)
+    }
+}
+
+/// Change the slot of the local player. The host applies it directly,
+/// clients also forward it to the host.
+pub fn update_local_slot(
+    session: &mut NetSession,
+    lobby: &mut Lobby,
+    update: impl FnOnce(&mut LobbySlot),
+) {
+    let Some(slot) = lobby.slot_mut(session.local) else {
+        return;
+    };
+    update(slot);
+
+    if !session.is_host() {
+        let empire = slot.empire.clone();
+        let ready = slot.ready;
+        session.broadcast(&NetMessage::LobbyEmpire(empire));
+        session.broadcast(&NetMessage::LobbyReady(ready));
+    }
+}
+
+/// Add and remove slots as players come and go.
+/// # Schedule
+/// [Update], on the host in [crate::AppState::InMenu].
+pub fn track_players(mut er_net: EventReader<NetEvent>, mut lobby: ResMut<Lobby>) {
+    for event in er_net.read() {
+        match event {
+            NetEvent::PlayerJoined(player) => lobby.join(*player),
+            NetEvent::PlayerLeft(player) => lobby.leave(*player),
+            _ => {}
+        }
+    }
+}
+
+/// Send the lobby to all clients after it changed.
+/// # Schedule
+/// [Update], on the host in [crate::AppState::InMenu], after the lobby
+/// changed.
+pub fn broadcast(mut session: ResMut<NetSession>, lobby: Res<Lobby>) {
+    session.broadcast(&NetMessage::Lobby(lobby.clone()));
+}
+
+/// Remove a player from the session.
+pub fn kick(session: &mut NetSession, lobby: &mut Lobby, player: PlayerId) {
+    if session.is_host() && player != session.local {
+        session.kick(player);
+        lobby.leave(player);
+    }
+}
@@ -0,0 +1,300 @@
+//! Deterministic lockstep on top of [NetSession].
+//!
+//! Local commands issued during tick `t` are scheduled for tick
+//! `t + INPUT_DELAY` and sent to all peers. A tick is only executed once
+//! the batches of all players for that tick are received, so every peer
+//! runs the same commands in the same order. Every `HASH_INTERVAL` ticks,
+//! clients report a hash of their state to the host, which compares it
+//! with its own to detect desyncs.
+
+use super::protocol::{CommandBatch, NetMessage};
+use super::{NetEvent, NetSession};
+use crate::command::{GameCommand, PendingCommands, PlayerId, TickCommands};
+use crate::tick::{GameTick, TickStep};
+use crate::utils::ObjectId;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// The number of ticks between issuing a command and executing it.
+pub const INPUT_DELAY: u64 = 2;
+
+/// The number of ticks between two state hashes.
+pub const HASH_INTERVAL: u64 = 10;
+
+/// The lockstep bookkeeping of an online game.
+#[derive(Resource, Debug)]
+pub struct Lockstep {
+    /// the next tick which the local batch is not sent yet.
+    next_batch: u64,
+    /// the players in lockstep, and the first tick they send batches for.
+    players: BTreeMap<PlayerId, u64>,
+    batches: BTreeMap<u64, BTreeMap<PlayerId, Vec<GameCommand>>>,
+    hashes: BTreeMap<u64, BTreeMap<PlayerId, u64>>,
+}
+
+/// The part of [Lockstep] a player needs to join a running game.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct LockstepSnapshot {
+    pub players: Vec<(PlayerId, u64)>,
+    pub batches: Vec<CommandBatch>,
+}
+
+impl Lockstep {
+    /// Start a new game with all players.
+    pub fn new(players: impl IntoIterator<Item = PlayerId>) -> Self {
+        Self {
+            // nobody can issue commands before the game starts, so the
+            // first ticks are always empty.
+            next_batch: INPUT_DELAY,
+            players: players.into_iter().map(|p| (p, 0)).collect(),
+            batches: BTreeMap::new(),
+            hashes: BTreeMap::new(),
+        }
+    }
+
+    /// Resume a running game from the snapshot of the host. The local
+    /// player does not send any batch until it is admitted with
+    /// [Lockstep::join].
+    pub fn resume(snapshot: LockstepSnapshot) -> Self {
+        let mut lockstep = Self {
+            next_batch: u64::MAX,
+            players: snapshot.players.into_iter().collect(),
+            batches: BTreeMap::new(),
+            hashes: BTreeMap::new(),
+        };
+        snapshot
+            .batches
+            .into_iter()
+            .for_each(|batch| lockstep.insert(batch));
+        lockstep
+    }
+
+    pub fn snapshot(&self) -> LockstepSnapshot {
+        LockstepSnapshot {
+            players: self.players.iter().map(|(p, t)| (*p, *t)).collect(),
+            batches: self
+                .batches
+                .iter()
+                .flat_map(|(tick, batches)| {
+                    batches.iter().map(|(player, commands)| CommandBatch {
+                        tick: *tick,
+                        player: *player,
+                        commands: commands.clone(),
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Whether the local player waits to be admitted.
+    pub fn is_joining(&self) -> bool {
+        self.next_batch == u64::MAX
+    }
+
+    /// The first tick the local player can still send a batch for.
+    pub fn next_batch(&self) -> u64 {
+        self.next_batch
+    }
+
+    /// Require the batches of the player from `tick` on.
+    pub fn join(&mut self, player: PlayerId, tick: u64, local: PlayerId) {
+        self.players.insert(player, tick);
+        if player == local {
+            self.next_batch = tick;
+        }
+    }
+
+    /// Whether the batches of the player are required, now or from a later
+    /// tick.
+    pub fn is_player(&self, player: PlayerId) -> bool {
+        self.players.contains_key(&player)
+    }
+
+    /// Stop waiting for the batches of the player.
+    pub fn leave(&mut self, player: PlayerId) {
+        self.players.remove(&player);
+    }
+
+    /// The last tick any batch is received for. A peer far behind it is
+    /// catching up with the game.
+    pub fn latest_tick(&self) -> Option<u64> {
+        self.batches.last_key_value().map(|(tick, _)| *tick)
+    }
+
+    /// Store the batch of a player.
+    pub fn insert(&mut self, batch: CommandBatch) {
+        self.batches
+            .entry(batch.tick)
+            .or_default()
+            .insert(batch.player, batch.commands);
+    }
+
+    /// Whether the batches of all players for the tick are received.
+    pub fn is_complete(&self, tick: u64) -> bool {
+        let batches = self.batches.get(&tick);
+        tick < INPUT_DELAY
+            || self
+                .players
+                .iter()
+                .filter(|(_, first)| **first <= tick)
+                .all(|(p, _)| batches.is_some_and(|batches| batches.contains_key(p)))
+    }
+
+    /// Remove the batches of a tick and return them, ordered by player.
+    pub fn take(&mut self, tick: u64) -> Vec<(PlayerId, GameCommand)> {
+        self.batches
+            .remove(&tick)
+            .unwrap_or_default()
+            .into_iter()
+            .flat_map(|(player, commands)| commands.into_iter().map(move |c| (player, c)))
+            .collect()
+    }
+
+    /// Record the state hash of a player. Return the players whose hash
+    /// differs from the one of `local`.
+    pub fn record_hash(
+        &mut self,
+        tick: u64,
+        player: PlayerId,
+        hash: u64,
+        local: PlayerId,
+    ) -> Vec<PlayerId> {
+        // forget ticks that are long settled.
+        self.hashes = self
+            .hashes
+            .split_off(&tick.saturating_sub(HASH_INTERVAL * 16));
+
+        let hashes = self.hashes.entry(tick).or_default();
+        hashes.insert(player, hash);
+
+        let Some(&expected) = hashes.get(&local) else {
+            return Vec::new();
+        };
+        if player == local {
+            hashes
+                .iter()
+                .filter(|(_, hash)| **hash != expected)
+                .map(|(player, _)| *player)
+                .collect()
+        } else if hash != expected {
+            vec![player]
+        } else {
+            Vec::new()
+        }
+    }
+}
+
+/// Send the local commands, scheduled [INPUT_DELAY] ticks ahead.
+/// # Schedule
+/// [FixedUpdate], in [crate::tick::TickSet::Sync], before [sync].
+pub fn submit(
+    tick: Res<GameTick>,
+    mut session: ResMut<NetSession>,
+    mut lockstep: ResMut<Lockstep>,
+    mut pending: ResMut<PendingCommands>,
+) {
+    if lockstep.next_batch > tick.0 + INPUT_DELAY {
+        return;
+    }
+
+    let batch = CommandBatch {
+        tick: lockstep.next_batch,
+        player: session.local,
+        commands: pending.0.drain(..).collect(),
+    };
+    lockstep.next_batch += 1;
+    session.broadcast(&NetMessage::Commands(batch.clone()));
+    lockstep.insert(batch);
+}
+
+/// Allow the tick to run once all batches for it are received.
+/// # Schedule
+/// [FixedUpdate], in [crate::tick::TickSet::Sync].
+pub fn sync(
+    tick: Res<GameTick>,
+    mut lockstep: ResMut<Lockstep>,
+    mut tick_commands: ResMut<TickCommands>,
+    mut step: ResMut<TickStep>,
+) {
+    if lockstep.is_complete(tick.0) {
+        tick_commands.0 = lockstep.take(tick.0);
+        step.ready = true;
+    } else {
+        debug!("Waiting for commands of tick {}", tick.0);
+    }
+}
+
+/// Hash the state of all game objects.
+pub fn state_hash(tick: GameTick, q_object: &Query<(&ObjectId, &Transform)>) -> u64 {
+    let mut objects: Vec<_> = q_object.iter().collect();
+    objects.sort_by_key(|(id, _)| id.0);
+
+    let mut hasher = DefaultHasher::new();
+    tick.hash(&mut hasher);
+    for (id, transform) in objects {
+        id.hash(&mut hasher);
+        for value in transform.translation.to_array() {
+            value.to_bits().hash(&mut hasher);
+        }
+        for value in transform.rotation.to_array() {
+            value.to_bits().hash(&mut hasher);
+        }
+    }
+    hasher.finish()
+}
+
+/// Compute the state hash after the tick, and compare or report it.
+/// # Schedule
+/// [FixedUpdate], in [crate::tick::TickSet::Advance], before
+/// [crate::tick::advance].
+pub fn check(
+    tick: Res<GameTick>,
+    mut session: ResMut<NetSession>,
+    mut lockstep: ResMut<Lockstep>,
+    mut ew_net: EventWriter<NetEvent>,
+    q_object: Query<(&ObjectId, &Transform)>,
+) {
+    if !tick.0.is_multiple_of(HASH_INTERVAL) {
+        return;
+    }
+
+    let hash = state_hash(*tick, &q_object);
+    let local = session.local;
+
+    if session.is_host() {
+        for player in lockstep.record_hash(tick.0, local, hash, local) {
+            report_desync(&mut session, &mut ew_net, tick.0, player);
+        }
+    } else {
+        session.broadcast(&NetMessage::StateHash {
+            tick: tick.0,
+            player: local,
+            hash,
+        });
+    }
+}
+
+/// Tell everyone that the state of `player` diverged.
+pub(super) fn report_desync(
+    session: &mut NetSession,
+    ew_net: &mut EventWriter<NetEvent>,
+    tick: u64,
+    player: PlayerId,
+) {
+    error!("Desync detected at tick {} for {:?}", tick, player);
+    session.broadcast(&NetMessage::Desync { tick, player });
+    ew_net.send(NetEvent::Desync { tick, player });
+}
+
+/// Stop waiting for players who left.
+/// # Schedule
+/// [Update], in an online game.
+pub fn track_players(mut er_net: EventReader<NetEvent>, mut lockstep: ResMut<Lockstep>) {
+    for event in er_net.read() {
+        if let NetEvent::PlayerLeft(player) = event {
+            lockstep.leave(*player);
+        }
+    }
+}
@@ -0,0 +1,427 @@
+//! Online multiplayer.
+//!
+//! One player hosts a game on a local port and the others join it. Players
+//! gather in the [Lobby], then the host distributes it so every peer
+//! generates the same map, and the game runs in deterministic lockstep,
+//! see [lockstep]. Players can also join a game that already started, see
+//! [rejoin].
+//!
+//! For testing, a session can be started from the command line:
+//! - `stellaris --host 7777 --players 2` hosts on port 7777 and starts
+//!   once 2 players (including the host) are present.
+//! - `stellaris --join 127.0.0.1:7777` joins it.
+
+mod connection;
+pub mod lobby;
+pub mod lockstep;
+pub mod protocol;
+pub mod rejoin;
+mod session;
+
+pub use lobby::Lobby;
+pub use lockstep::Lockstep;
+pub use session::NetSession;
+
+use crate::command::{PendingCommands, PlayerId};
+use crate::empire::{spawn_empires, Difficulty};
+use crate::game_map::galaxy::{Galaxy, PrimaryGalaxy};
+use crate::save::SaveGame;
+use crate::states::{AppState, LoadSource};
+use crate::tick::{self, TickSet};
+use bevy::prelude::*;
+use protocol::NetMessage;
+use std::io;
+use std::net::ToSocketAddrs;
+
+/// Notifications about the online session.
+#[derive(Event, Clone, Debug)]
+pub enum NetEvent {
+    /// A player joined the session. On a client, this is also sent for
+    /// the local player once the host accepted it.
+    PlayerJoined(PlayerId),
+    /// A player left the session.
+    PlayerLeft(PlayerId),
+    /// The connection to the host is lost.
+    Disconnected,
+    /// The state of a player diverged from the host at the tick.
+    Desync { tick: u64, player: PlayerId },
+}
+
+/// An online session requested from the command line.
+#[derive(Resource, Clone, Debug)]
+pub enum NetLaunch {
+    Host { port: u16, players: usize },
+    Join { addr: String },
+}
+
+impl NetLaunch {
+    /// Parse `--host <port> [--players <n>]` or `--join <addr>`.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Option<Self> {
+        let args: Vec<String> = args.into_iter().collect();
+        let value = |flag: &str| {
+            args.iter()
+                .position(|arg| arg == flag)
+                .and_then(|i| args.get(i + 1))
+        };
+
+        if let Some(port) = value("--host") {
+            let Ok(port) = port.parse() else {
+                error!("Invalid port after --host: {}", port);
+                return None;
+            };
+            let players = match value("--players").map(|n| n.parse()) {
+                None => 2,
+                Some(Ok(players)) => players,
+                Some(Err(e)) => {
+                    error!("Invalid --players: {}", e);
+                    return None;
+                }
+            };
+            Some(Self::Host { port, players })
+        } else {
+            value("--join").map(|addr| Self::Join { addr: addr.clone() })
+        }
+    }
+}
+
+/// The plugin for online multiplayer.
+pub struct NetPlugin;
+
+impl Plugin for NetPlugin {
+    fn build(&self, app: &mut App) {
+        let in_session = resource_exists::<NetSession>;
+        let is_host = |session: Option<Res<NetSession>>| session.is_some_and(|s| s.is_host());
+        let in_lockstep = || {
+            in_session
+                .and_then(resource_exists::<Lockstep>)
+                .and_then(in_state(AppState::InGame))
+        };
+        let is_joining = |lockstep: Option<Res<Lockstep>>| lockstep.is_some_and(|l| l.is_joining());
+
+        app.add_event::<NetEvent>()
+            .add_systems(
+                OnEnter(AppState::InMenu),
+                launch.run_if(resource_exists::<NetLaunch>),
+            )
+            .add_systems(PreUpdate, receive.run_if(in_session))
+            .add_systems(
+                PreUpdate,
+                rejoin::catch_up.after(receive).run_if(in_lockstep()),
+            )
+            .add_systems(
+                OnEnter(AppState::InGame),
+                (
+                    rejoin::announce_loaded.run_if(in_session.and_then(is_joining)),
+                    rejoin::send_queued_snapshots
+                        .run_if(is_host.and_then(resource_exists::<Lockstep>)),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    lockstep::track_players,
+                    rejoin::send_snapshots.run_if(is_host),
+                )
+                    .run_if(in_lockstep()),
+            )
+            .add_systems(
+                Update,
+                (
+                    lobby::track_players,
+                    lobby::broadcast.run_if(resource_exists_and_changed::<Lobby>),
+                    start_when_full.run_if(resource_exists::<NetLaunch>),
+                )
+                    .chain()
+                    .run_if(is_host.and_then(resource_exists::<Lobby>))
+                    .run_if(in_state(AppState::InMenu)),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    (lockstep::submit, lockstep::sync)
+                        .chain()
+                        .in_set(TickSet::Sync),
+                    lockstep::check
+                        .in_set(TickSet::Advance)
+                        .before(tick::advance)
+                        .run_if(tick::tick_ready),
+                )
+                    .run_if(in_lockstep()),
+            );
+    }
+}
+
+/// Host a new session with an empty lobby.
+pub fn host(commands: &mut Commands, port: u16) -> io::Result<()> {
+    let session = NetSession::host(port)?;
+    commands.insert_resource(Lobby::new(session.local));
+    commands.insert_resource(session);
+    Ok(())
+}
+
+/// Join a session, the lobby is received from the host later.
+pub fn join(commands: &mut Commands, addr: impl ToSocketAddrs) -> io::Result<()> {
+    commands.insert_resource(NetSession::join(addr)?);
+    Ok(())
+}
+
+/// Leave the current session, if any.
+pub fn leave(commands: &mut Commands) {
+    commands.remove_resource::<NetSession>();
+    commands.remove_resource::<Lobby>();
+    commands.remove_resource::<Lockstep>();
+}
+
+/// Open the session requested by [NetLaunch].
+/// # Schedule
+/// Enter [AppState::InMenu].
+fn launch(mut commands: Commands, launch: Res<NetLaunch>) {
+    let result = match launch.as_ref() {
+        NetLaunch::Host { port, .. } => host(&mut commands, *port),
+        NetLaunch::Join { addr } => join(&mut commands, addr.as_str()),
+    };
+
+    if let Err(e) = result {
+        error!("Fail to start online session {:?}: {}", *launch, e);
+    }
+}
+
+/// Start the game once enough players joined, without waiting for them
+/// to be ready.
+/// # Schedule
+/// [Update], on the host in [AppState::InMenu] with a [NetLaunch].
+fn start_when_full(
+    mut commands: Commands,
+    mut session: ResMut<NetSession>,
+    launch: Res<NetLaunch>,
+    lobby: Res<Lobby>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if let NetLaunch::Host { players, .. } = *launch {
+        if lobby.slots.len() >= players {
+            commands.remove_resource::<NetLaunch>();
+            start_game(&mut commands, &mut session, &mut app_state, &lobby);
+        }
+    }
+}
+
+/// Tell all clients to start, and start generating the map locally. Only
+/// the host can do this.
+pub fn start_game(
+    commands: &mut Commands,
+    session: &mut NetSession,
+    app_state: &mut NextState<AppState>,
+    lobby: &Lobby,
+) {
+    assert!(session.is_host(), "Only the host can start an online game.");
+    session.players = lobby.players();
+    session.broadcast(&NetMessage::Start(lobby.clone()));
+    commands.insert_resource(Lockstep::new(lobby.players()));
+    enter_loading(commands, app_state, lobby);
+}
+
+fn enter_loading(commands: &mut Commands, app_state: &mut NextState<AppState>, lobby: &Lobby) {
+    spawn_empires(
+        commands,
+        lobby.params.seed,
+        lobby
+            .slots
+            .iter()
+            .map(|slot| (Some(slot.player), slot.empire.clone())),
+    );
+    commands.insert_resource(Difficulty::default());
+    commands.spawn((Galaxy, PrimaryGalaxy, lobby.params.clone()));
+    app_state.set(AppState::Loading(LoadSource::FromOnline));
+}
+
+/// Exchange data with peers and dispatch the received messages.
+/// # Schedule
+/// [PreUpdate], if [NetSession] exists.
+#[allow(clippy::too_many_arguments)]
+fn receive(
+    mut commands: Commands,
+    mut session: ResMut<NetSession>,
+    mut lockstep: Option<ResMut<Lockstep>>,
+    mut lobby: Option<ResMut<Lobby>>,
+    mut pending: ResMut<PendingCommands>,
+    mut app_state: ResMut<NextState<AppState>>,
+    mut ew_net: EventWriter<NetEvent>,
+    q_empire: rejoin::EmpireQuery,
+) {
+    // the lockstep of a game started by this poll. Batches can arrive
+    // right after the start, before the resource is inserted.
+    let mut started = None;
+
+    for (sender, message) in session.poll(&mut ew_net) {
+        let lockstep = lockstep.as_deref_mut().or(started.as_mut());
+
+        match message {
+            NetMessage::Lobby(lobby) => {
+                commands.insert_resource(lobby);
+            }
+            NetMessage::LobbyEmpire(empire) => {
+                if let Some(slot) = lobby.as_mut().and_then(|l| l.slot_mut(sender)) {
+                    slot.empire = empire;
+                }
+            }
+            NetMessage::LobbyReady(ready) => {
+                if let Some(slot) = lobby.as_mut().and_then(|l| l.slot_mut(sender)) {
+                    slot.ready = ready;
+                }
+            }
+            NetMessage::Start(lobby) => {
+                session.players = lobby.players();
+                started = Some(Lockstep::new(lobby.players()));
+                enter_loading(&mut commands, &mut app_state, &lobby);
+                commands.insert_resource(lobby);
+            }
+            NetMessage::Snapshot {
+                save,
+                players,
+                lockstep: snapshot,
+            } => {
+                info!("Joining the running game at tick {}", save.tick.0);
+                session.players = players;
+                started = Some(Lockstep::resume(snapshot));
+                commands.spawn((Galaxy, PrimaryGalaxy, save.params.clone()));
+                commands.insert_resource(SaveGame::clone(&save));
+                app_state.set(AppState::Loading(LoadSource::FromOnline));
+            }
+            NetMessage::Loaded if session.is_host() => match lockstep {
+                Some(lockstep) => {
+                    rejoin::admit(&mut session, lockstep, &mut pending, &q_empire, sender)
+                }
+                None => warn!("{:?} loaded, but the game is not started", sender),
+            },
+            NetMessage::PlayerJoined { player, tick } => {
+                if let Some(lockstep) = lockstep {
+                    lockstep.join(player, tick, session.local);
+                }
+                if !session.players.contains(&player) {
+                    session.players.push(player);
+                }
+                ew_net.send(NetEvent::PlayerJoined(player));
+            }
+            NetMessage::Commands(batch) => {
+                if session.is_host() {
+                    session.broadcast_except(sender, &NetMessage::Commands(batch.clone()));
+                }
+                match lockstep {
+                    Some(lockstep) => lockstep.insert(batch),
+                    None => warn!("Drop commands of {:?}, game is not started", batch.player),
+                }
+            }
+            NetMessage::StateHash { tick, player, hash } => {
+                let local = session.local;
+                let desynced = match lockstep {
+                    Some(lockstep) => lockstep.record_hash(tick, player, hash, local),
+                    None => Vec::new(),
+                };
+                for player in desynced {
+                    lockstep::report_desync(&mut session, &mut ew_net, tick, player);
+                }
+            }
+            NetMessage::Desync { tick, player } => {
+                error!("Host reported desync at tick {} for {:?}", tick, player);
+                ew_net.send(NetEvent::Desync { tick, player });
+            }
+            NetMessage::PlayerLeft { player } => {
+                session.players.retain(|p| *p != player);
+                ew_net.send(NetEvent::PlayerLeft(player));
+            }
+            message => warn!("Unexpected message {:?} from {:?}", message, sender),
+        }
+    }
+
+    if let Some(lockstep) = started {
+        commands.insert_resource(lockstep);
+    }
+
+    if session.is_closed() {
+        leave(&mut commands);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tick::GameTick;
+    use crate::utils::ObjectId;
+    use bevy::state::app::StatesPlugin;
+    use std::net::{Ipv4Addr, TcpListener};
+    use std::thread;
+    use std::time::Duration;
+
+    /// The desyncs reported to a peer.
+    #[derive(Resource, Default)]
+    struct Desyncs(Vec<(u64, PlayerId)>);
+
+    fn record_desyncs(mut er_net: EventReader<NetEvent>, mut desyncs: ResMut<Desyncs>) {
+        for event in er_net.read() {
+            if let NetEvent::Desync { tick, player } = event {
+                desyncs.0.push((*tick, *player));
+            }
+        }
+    }
+
+    /// A peer in lockstep with two players, its state is one object at `x`.
+    fn peer(session: NetSession, object: ObjectId, x: f32) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .init_state::<AppState>()
+            .add_event::<NetEvent>()
+            .init_resource::<PendingCommands>()
+            .init_resource::<Desyncs>()
+            .insert_resource(GameTick(1))
+            .insert_resource(Lockstep::new([PlayerId(0), PlayerId(1)]))
+            .insert_resource(session)
+            .add_systems(Update, (receive, lockstep::check, record_desyncs).chain());
+        app.world_mut()
+            .spawn((object, Transform::from_xyz(x, 0.0, 0.0)));
+        app
+    }
+
+    /// Update both peers until `done`, or panic after a while.
+    fn run_until(host: &mut App, client: &mut App, done: impl Fn(&App, &App) -> bool) {
+        for _ in 0..500 {
+            host.update();
+            client.update();
+            if done(host, client) {
+                return;
+            }
+            thread::sleep(Duration::from_millis(2));
+        }
+        panic!("Peers did not get there in time.");
+    }
+
+    #[test]
+    fn report_desync_on_localhost() {
+        let port = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+            .and_then(|listener| listener.local_addr())
+            .unwrap()
+            .port();
+        let object = ObjectId::default();
+        let mut host = peer(NetSession::host(port).unwrap(), object, 0.0);
+        let mut client = peer(
+            NetSession::join((Ipv4Addr::LOCALHOST, port)).unwrap(),
+            object,
+            1.0,
+        );
+
+        run_until(&mut host, &mut client, |_, client| {
+            client.world().resource::<NetSession>().local == PlayerId(1)
+        });
+
+        // both peers hash their diverged states at the same tick.
+        host.insert_resource(GameTick(lockstep::HASH_INTERVAL));
+        client.insert_resource(GameTick(lockstep::HASH_INTERVAL));
+        run_until(&mut host, &mut client, |host, client| {
+            !host.world().resource::<Desyncs>().0.is_empty()
+                && !client.world().resource::<Desyncs>().0.is_empty()
+        });
+
+        let expected = (lockstep::HASH_INTERVAL, PlayerId(1));
+        assert_eq!(host.world().resource::<Desyncs>().0[0], expected);
+        assert_eq!(client.world().resource::<Desyncs>().0[0], expected);
+    }
+}
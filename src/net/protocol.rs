@@ -0,0 +1,100 @@
+//! Messages exchanged between peers and their wire format.
+//!
+//! Every message is a frame of a 4-byte big-endian length followed by
+//! the JSON encoding of [NetMessage].
+
+use super::lobby::Lobby;
+use super::lockstep::LockstepSnapshot;
+use crate::command::{GameCommand, PlayerId};
+use crate::empire::EmpireSetup;
+use crate::save::SaveGame;
+use serde::{Deserialize, Serialize};
+use std::io;
+
+/// The largest frame we accept, anything larger is treated as corrupted.
+pub const MAX_FRAME_SIZE: usize = 64 * 1024 * 1024;
+
+/// The version string peers must agree on.
+pub const PROTOCOL_VERSION: &str = env!("CARGO_PKG_VERSION");
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum NetMessage {
+    /// client -> host, the first message after connecting.
+    Hello { version: String },
+    /// host -> client, accept the connection and assign the player id.
+    Welcome { player: PlayerId },
+    /// host -> client, refuse the connection.
+    Reject { reason: String },
+    /// host -> client, the client is removed from the session.
+    Kicked,
+    /// host -> client, the current state of the lobby.
+    Lobby(Lobby),
+    /// client -> host, change the empire of the sender.
+    LobbyEmpire(EmpireSetup),
+    /// client -> host, change whether the sender is ready.
+    LobbyReady(bool),
+    /// host -> client, start generating the map of the lobby.
+    Start(Lobby),
+    /// The commands of a player for a tick. Clients send their batches to
+    /// the host, which relays them to everyone else.
+    Commands(CommandBatch),
+    /// client -> host, the state hash after a tick.
+    StateHash {
+        tick: u64,
+        player: PlayerId,
+        hash: u64,
+    },
+    /// host -> client, the state of a player diverged from the host.
+    Desync { tick: u64, player: PlayerId },
+    /// host -> client, a player has left the game.
+    PlayerLeft { player: PlayerId },
+    /// host -> client, the game to resume when joining after it started.
+    Snapshot {
+        save: Box<SaveGame>,
+        players: Vec<PlayerId>,
+        lockstep: LockstepSnapshot,
+    },
+    /// client -> host, the snapshot is loaded.
+    Loaded,
+    /// host -> client, a player joining late sends batches from the tick on.
+    PlayerJoined { player: PlayerId, tick: u64 },
+}
+
+/// All commands a player issues for one tick. An empty batch still
+/// needs to be sent, otherwise other peers can not advance.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CommandBatch {
+    pub tick: u64,
+    pub player: PlayerId,
+    pub commands: Vec<GameCommand>,
+}
+
+/// Append the frame of `message` to `buffer`.
+pub fn encode(message: &NetMessage, buffer: &mut Vec<u8>) {
+    let payload = serde_json::to_vec(message).expect("Fail to serialize net message.");
+    buffer.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+    buffer.extend_from_slice(&payload);
+}
+
+/// Take the first complete frame out of `buffer`, if any.
+pub fn decode(buffer: &mut Vec<u8>) -> io::Result<Option<NetMessage>> {
+    if buffer.len() < 4 {
+        return Ok(None);
+    }
+
+    let len = u32::from_be_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as usize;
+    if len > MAX_FRAME_SIZE {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("frame of {} bytes is too large", len),
+        ));
+    }
+    if buffer.len() < 4 + len {
+        return Ok(None);
+    }
+
+    let message = serde_json::from_slice(&buffer[4..4 + len])
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    buffer.drain(..4 + len);
+    Ok(Some(message))
+}
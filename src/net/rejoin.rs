@@ -0,0 +1,165 @@
+//! Players joining a game that already started.
+//!
+//! When a client connects to a running game, the host sends it a
+//! [SaveGame] of the current tick along with the buffered command batches.
+//! The client generates the map and restores the save like any other
+//! load, then tells the host it has loaded. The host hands it a vacant
+//! empire and admits it into the lockstep from its next batch on, while
+//! the client runs ticks faster than real time until it caught up.
+//! Clients connecting while the host still loads the game wait for the
+//! snapshot until the host is in game.
+//!
+//! [SaveGame]: crate::save::SaveGame
+
+use super::lockstep::{Lockstep, INPUT_DELAY};
+use super::protocol::NetMessage;
+use super::{NetEvent, NetSession};
+use crate::command::{GameCommand, PendingCommands, PlayerId};
+use crate::empire::{Empire, PlayerControlled};
+use crate::save::SaveQuery;
+use crate::tick::{GameTick, TICKS_PER_SECOND};
+use crate::utils::ObjectId;
+use bevy::prelude::*;
+use std::time::Duration;
+
+/// How many times faster than usual ticks run while catching up.
+pub const CATCH_UP_SPEED: f64 = 10.0;
+
+pub type EmpireQuery<'w, 's> =
+    Query<'w, 's, (&'static ObjectId, Option<&'static PlayerControlled>), With<Empire>>;
+
+/// Find an empire for a new player: the empire of a player who left,
+/// or else one controlled by the AI.
+fn vacant_empire(session: &NetSession, q_empire: &EmpireQuery) -> Option<ObjectId> {
+    let left = q_empire
+        .iter()
+        .find(|(_, player)| player.is_some_and(|player| !session.players.contains(&player.0)));
+    let ai = q_empire.iter().find(|(_, player)| player.is_none());
+    left.or(ai).map(|(id, _)| *id)
+}
+
+fn refuse(session: &mut NetSession, player: PlayerId) {
+    session.send_to(
+        player,
+        &NetMessage::Reject {
+            reason: "No empire is left to play.".to_string(),
+        },
+    );
+    session.kick(player);
+}
+
+/// Send a snapshot of the game to a player, or refuse it if no empire is
+/// left to play.
+fn send_snapshot(
+    session: &mut NetSession,
+    lockstep: &Lockstep,
+    save: &SaveQuery,
+    q_empire: &EmpireQuery,
+    player: PlayerId,
+) {
+    if vacant_empire(session, q_empire).is_none() {
+        refuse(session, player);
+        return;
+    }
+    let Some(save) = save.save() else {
+        return;
+    };
+
+    info!("Sending snapshot of tick {} to {:?}", save.tick.0, player);
+    let players = session.players.clone();
+    session.send_to(
+        player,
+        &NetMessage::Snapshot {
+            save: Box::new(save),
+            players,
+            lockstep: lockstep.snapshot(),
+        },
+    );
+}
+
+/// Send a snapshot of the game to players connecting after it started.
+/// # Schedule
+/// [Update], on the host in [crate::AppState::InGame].
+pub fn send_snapshots(
+    mut er_net: EventReader<NetEvent>,
+    mut session: ResMut<NetSession>,
+    lockstep: Res<Lockstep>,
+    save: SaveQuery,
+    q_empire: EmpireQuery,
+) {
+    for event in er_net.read() {
+        if let NetEvent::PlayerJoined(player) = *event {
+            send_snapshot(&mut session, &lockstep, &save, &q_empire, player);
+        }
+    }
+}
+
+/// Send a snapshot of the game to players who connected while the host
+/// was loading it.
+/// # Schedule
+/// Enter [crate::AppState::InGame], on the host.
+pub fn send_queued_snapshots(
+    mut session: ResMut<NetSession>,
+    lockstep: Res<Lockstep>,
+    save: SaveQuery,
+    q_empire: EmpireQuery,
+) {
+    let queued: Vec<_> = session
+        .players
+        .iter()
+        .filter(|player| !lockstep.is_player(**player))
+        .copied()
+        .collect();
+    for player in queued {
+        send_snapshot(&mut session, &lockstep, &save, &q_empire, player);
+    }
+}
+
+/// Tell the host the snapshot is loaded.
+/// # Schedule
+/// Enter [crate::AppState::InGame], on a client joining a running game.
+pub fn announce_loaded(mut session: ResMut<NetSession>) {
+    session.broadcast(&NetMessage::Loaded);
+}
+
+/// Give a vacant empire to a player who loaded the snapshot, and require
+/// its batches from the next batch of the host on.
+pub(super) fn admit(
+    session: &mut NetSession,
+    lockstep: &mut Lockstep,
+    pending: &mut PendingCommands,
+    q_empire: &EmpireQuery,
+    player: PlayerId,
+) {
+    let Some(empire) = vacant_empire(session, q_empire) else {
+        refuse(session, player);
+        return;
+    };
+
+    let tick = lockstep.next_batch();
+    pending
+        .0
+        .push(GameCommand::ControlEmpire { empire, player });
+    lockstep.join(player, tick, session.local);
+    session.broadcast(&NetMessage::PlayerJoined { player, tick });
+    info!("{:?} joins the game at tick {}", player, tick);
+}
+
+/// Run ticks faster while the local game is far behind the other peers.
+/// # Schedule
+/// [PreUpdate], in an online game.
+pub fn catch_up(tick: Res<GameTick>, lockstep: Res<Lockstep>, mut time: ResMut<Time<Fixed>>) {
+    let behind = lockstep
+        .latest_tick()
+        .is_some_and(|latest| latest > tick.0 + 2 * INPUT_DELAY);
+    let hz = if behind {
+        TICKS_PER_SECOND * CATCH_UP_SPEED
+    } else {
+        TICKS_PER_SECOND
+    };
+
+    let timestep = Duration::from_secs_f64(1.0 / hz);
+    if time.timestep() != timestep {
+        time.set_timestep(timestep);
+    }
+}
@@ -0,0 +1,283 @@
+//! The connections of an online game.
+//!
+//! The session is a star: the host listens on a local port, every client
+//! connects to the host only, and the host relays messages between them.
+
+use super::connection::Connection;
+use super::protocol::{NetMessage, PROTOCOL_VERSION};
+use super::NetEvent;
+use crate::command::PlayerId;
+use bevy::prelude::*;
+use std::io;
+use std::net::{Ipv4Addr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// How long to wait for a host to accept the connection. Joining blocks
+/// the app meanwhile.
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// A connected client, seen from the host.
+struct Peer {
+    player: PlayerId,
+    connection: Connection,
+    /// whether the handshake is completed.
+    accepted: bool,
+}
+
+enum Role {
+    Host {
+        listener: TcpListener,
+        peers: Vec<Peer>,
+        next_id: u32,
+    },
+    Client {
+        host: Connection,
+    },
+}
+
+/// The resource of an active online session.
+#[derive(Resource)]
+pub struct NetSession {
+    /// The id of the local player. It is only valid on a client after the
+    /// host has welcomed it.
+    pub local: PlayerId,
+    /// All players in the game, including the local one.
+    pub players: Vec<PlayerId>,
+    role: Role,
+    closed: bool,
+}
+
+impl NetSession {
+    /// Start hosting on a local port.
+    pub fn host(port: u16) -> io::Result<Self> {
+        let listener = TcpListener::bind((Ipv4Addr::UNSPECIFIED, port))?;
+        listener.set_nonblocking(true)?;
+        info!("Hosting online game on {}", listener.local_addr()?);
+
+        Ok(Self {
+            local: PlayerId(0),
+            players: vec![PlayerId(0)],
+            role: Role::Host {
+                listener,
+                peers: Vec::new(),
+                next_id: 1,
+            },
+            closed: false,
+        })
+    }
+
+    /// Connect to a host, giving up after [CONNECT_TIMEOUT] for each of
+    /// its addresses.
+    pub fn join(addr: impl ToSocketAddrs) -> io::Result<Self> {
+        let mut stream = Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "no address to connect to",
+        ));
+        for addr in addr.to_socket_addrs()? {
+            stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT);
+            if stream.is_ok() {
+                break;
+            }
+        }
+        let mut host = Connection::new(stream?)?;
+        info!("Connected to online game on {}", host.peer_addr()?);
+        host.send(&NetMessage::Hello {
+            version: PROTOCOL_VERSION.to_string(),
+        });
+
+        Ok(Self {
+            local: PlayerId(0),
+            players: Vec::new(),
+            role: Role::Client { host },
+            closed: false,
+        })
+    }
+
+    pub fn is_host(&self) -> bool {
+        matches!(self.role, Role::Host { .. })
+    }
+
+    /// Whether the connection to the host is lost.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Send a message to the host, or to all clients if this is the host.
+    pub fn broadcast(&mut self, message: &NetMessage) {
+        self.broadcast_except(self.local, message);
+    }
+
+    /// Same as [NetSession::broadcast], but skip the given player.
+    pub fn broadcast_except(&mut self, player: PlayerId, message: &NetMessage) {
+        match &mut self.role {
+            Role::Host { peers, .. } => peers
+                .iter_mut()
+                .filter(|peer| peer.accepted && peer.player != player)
+                .for_each(|peer| peer.connection.send(message)),
+            Role::Client { host } => host.send(message),
+        }
+    }
+
+    /// Send a message to one player. Clients can only send to the host.
+    pub fn send_to(&mut self, player: PlayerId, message: &NetMessage) {
+        match &mut self.role {
+            Role::Host { peers, .. } => peers
+                .iter_mut()
+                .filter(|peer| peer.player == player)
+                .for_each(|peer| peer.connection.send(message)),
+            Role::Client { host } => host.send(message),
+        }
+    }
+
+    /// Disconnect a client. Only the host can do this.
+    pub fn kick(&mut self, player: PlayerId) {
+        let Role::Host { peers, .. } = &mut self.role else {
+            return;
+        };
+        if let Some(i) = peers.iter().position(|peer| peer.player == player) {
+            let mut peer = peers.remove(i);
+            peer.connection.send(&NetMessage::Kicked);
+            // best effort, the socket is closed right after.
+            let _ = peer.connection.flush();
+            info!("Kicked {:?}", player);
+        }
+        self.players.retain(|p| *p != player);
+        self.broadcast(&NetMessage::PlayerLeft { player });
+    }
+
+    /// Accept new clients, exchange data with all connections, and
+    /// return the received messages along with their sender.
+    pub fn poll(&mut self, ew_net: &mut EventWriter<NetEvent>) -> Vec<(PlayerId, NetMessage)> {
+        let mut received = Vec::new();
+
+        match &mut self.role {
+            Role::Host {
+                listener,
+                peers,
+                next_id,
+            } => {
+                loop {
+                    match listener.accept() {
+                        Ok((stream, addr)) => match Connection::new(stream) {
+                            Ok(connection) => {
+                                debug!("Incoming connection from {}", addr);
+                                peers.push(Peer {
+                                    player: PlayerId(*next_id),
+                                    connection,
+                                    accepted: false,
+                                });
+                                *next_id += 1;
+                            }
+                            Err(e) => warn!("Fail to set up connection from {}: {}", addr, e),
+                        },
+                        Err(e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                        Err(e) => {
+                            warn!("Fail to accept connection: {}", e);
+                            break;
+                        }
+                    }
+                }
+
+                let mut lost = Vec::new();
+                for peer in peers.iter_mut() {
+                    let messages = match peer.connection.receive() {
+                        Ok(messages) => messages,
+                        Err(e) => {
+                            info!("Lost connection to {:?}: {}", peer.player, e);
+                            lost.push(peer.player);
+                            continue;
+                        }
+                    };
+
+                    for message in messages {
+                        match message {
+                            NetMessage::Hello { version } if !peer.accepted => {
+                                if version == PROTOCOL_VERSION {
+                                    peer.accepted = true;
+                                    peer.connection.send(&NetMessage::Welcome {
+                                        player: peer.player,
+                                    });
+                                    self.players.push(peer.player);
+                                    ew_net.send(NetEvent::PlayerJoined(peer.player));
+                                } else {
+                                    peer.connection.send(&NetMessage::Reject {
+                                        reason: format!(
+                                            "Version mismatch, host is {}, client is {}",
+                                            PROTOCOL_VERSION, version
+                                        ),
+                                    });
+                                }
+                            }
+                            message if peer.accepted => received.push((peer.player, message)),
+                            message => warn!("Unexpected message {:?} before handshake", message),
+                        }
+                    }
+
+                    if let Err(e) = peer.connection.flush() {
+                        info!("Lost connection to {:?}: {}", peer.player, e);
+                        lost.push(peer.player);
+                    }
+                }
+
+                for player in lost {
+                    let accepted = peers
+                        .iter()
+                        .any(|peer| peer.player == player && peer.accepted);
+                    peers.retain(|peer| peer.player != player);
+                    if accepted {
+                        self.players.retain(|p| *p != player);
+                        peers
+                            .iter_mut()
+                            .filter(|peer| peer.accepted)
+                            .for_each(|peer| {
+                                peer.connection.send(&NetMessage::PlayerLeft { player })
+                            });
+                        ew_net.send(NetEvent::PlayerLeft(player));
+                    }
+                }
+            }
+            Role::Client { host } => {
+                if self.closed {
+                    return received;
+                }
+
+                let result = host.receive().and_then(|messages| {
+                    host.flush()?;
+                    Ok(messages)
+                });
+
+                match result {
+                    Ok(messages) => {
+                        for message in messages {
+                            match message {
+                                NetMessage::Welcome { player } => {
+                                    info!("Joined online game as {:?}", player);
+                                    self.local = player;
+                                    ew_net.send(NetEvent::PlayerJoined(player));
+                                }
+                                NetMessage::Reject { reason } => {
+                                    error!("Host refused the connection: {}", reason);
+                                    self.closed = true;
+                                    ew_net.send(NetEvent::Disconnected);
+                                }
+                                NetMessage::Kicked => {
+                                    warn!("Kicked by the host");
+                                    self.closed = true;
+                                    ew_net.send(NetEvent::Disconnected);
+                                }
+                                message => received.push((PlayerId(0), message)),
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        error!("Lost connection to host: {}", e);
+                        self.closed = true;
+                        ew_net.send(NetEvent::Disconnected);
+                    }
+                }
+            }
+        }
+
+        received
+    }
+}
@@ -0,0 +1,156 @@
+//! Save files, stored as RON in the data directory of the platform.
+//!
+//! A save file holds a [SaveMeta] header before the [SaveGame]. The header
+//! is read alone to list the saves, so that saves of another format are
+//! still listed, with a warning. A screenshot of the game is stored next
+//! to the save with the same name, as its thumbnail.
+
+use super::{SaveGame, SAVE_VERSION};
+use crate::command::PlayerId;
+use crate::tick::GameTick;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// What the save browser shows of a save.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SaveMeta {
+    /// the [SAVE_VERSION] the save is written with.
+    pub format: u32,
+    /// the version of the game that wrote the save.
+    pub game_version: String,
+    /// the name of the empire of the player.
+    pub empire: String,
+    pub tick: GameTick,
+    /// the real time spent playing, in seconds.
+    pub play_time: f64,
+    /// the time of the save, in seconds since the Unix epoch.
+    pub saved_at: u64,
+}
+
+impl SaveMeta {
+    /// Whether this version of the game can load the save.
+    pub fn is_compatible(&self) -> bool {
+        self.format == SAVE_VERSION
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct SaveFile {
+    pub meta: SaveMeta,
+    pub game: SaveGame,
+}
+
+/// The start of a [SaveFile], the game is skipped.
+#[derive(Deserialize)]
+struct SaveHeader {
+    meta: SaveMeta,
+}
+
+impl SaveFile {
+    /// Wrap a snapshot of the game, played by `player`.
+    pub fn new(game: SaveGame, player: PlayerId) -> Self {
+        let empire = game
+            .empires
+            .iter()
+            .find(|empire| empire.player == Some(player))
+            .or(game.empires.first())
+            .map(|empire| empire.setup.name.clone())
+            .unwrap_or_default();
+        let saved_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs());
+        let meta = SaveMeta {
+            format: game.version,
+            game_version: env!("CARGO_PKG_VERSION").to_string(),
+            empire,
+            tick: game.tick,
+            play_time: game.play_time,
+            saved_at,
+        };
+        Self { meta, game }
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        let text = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let file: Self = ron::from_str(&text).map_err(|e| e.to_string())?;
+        if !file.meta.is_compatible() {
+            return Err(format!(
+                "the save format {} is not supported",
+                file.meta.format
+            ));
+        }
+        Ok(file)
+    }
+
+    /// Write the save into `dir`, named after the time it is saved.
+    /// Return the path of the save.
+    pub fn write(&self, dir: &Path) -> Result<PathBuf, String> {
+        let text = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|e| e.to_string())?;
+        std::fs::create_dir_all(dir).map_err(|e| e.to_string())?;
+
+        // several saves within a second get a suffix.
+        let mut path = dir.join(format!("{}.ron", self.meta.saved_at));
+        for i in 1.. {
+            if !path.exists() {
+                break;
+            }
+            path = dir.join(format!("{}-{}.ron", self.meta.saved_at, i));
+        }
+        std::fs::write(&path, text).map_err(|e| e.to_string())?;
+        Ok(path)
+    }
+}
+
+/// A save found in the save directory.
+#[derive(Clone, Debug)]
+pub struct SaveEntry {
+    pub path: PathBuf,
+    /// the error if the header cannot be read.
+    pub meta: Result<SaveMeta, String>,
+}
+
+impl SaveEntry {
+    /// The path of the screenshot of the save.
+    pub fn thumbnail(&self) -> PathBuf {
+        self.path.with_extension("png")
+    }
+}
+
+/// The directory of the saves, `None` if the platform has no data directory.
+pub fn saves_dir() -> Option<PathBuf> {
+    ProjectDirs::from("", "", "stellaris").map(|dirs| dirs.data_dir().join("saves"))
+}
+
+/// The saves in `dir`, in no particular order.
+pub fn list_saves(dir: &Path) -> Result<Vec<SaveEntry>, String> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(error) => return Err(error.to_string()),
+    };
+    let saves = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+        .map(|path| {
+            let meta = std::fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|text| ron::from_str::<SaveHeader>(&text).map_err(|e| e.to_string()))
+                .map(|header| header.meta);
+            SaveEntry { path, meta }
+        })
+        .collect();
+    Ok(saves)
+}
+
+/// Delete a save and its screenshot.
+pub fn delete_save(entry: &SaveEntry) -> Result<(), String> {
+    std::fs::remove_file(&entry.path).map_err(|e| e.to_string())?;
+    match std::fs::remove_file(entry.thumbnail()) {
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error.to_string()),
+        _ => Ok(()),
+    }
+}
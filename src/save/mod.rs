@@ -0,0 +1,235 @@
+//! Snapshots of a running game.
+//!
+//! The map itself is not stored: it is regenerated from its
+//! [GalaxyGenParams], which always yields the same systems with the same
+//! ids. A [SaveGame] only records those parameters and the state that
+//! changes during the game. To load one, insert it as a resource along
+//! with the galaxy to generate, and [restore] applies it once the map is
+//! ready.
+//!
+//! [file] stores saves on the disk, along with what the save browser shows
+//! of them.
+
+mod file;
+
+pub use file::{delete_save, list_saves, saves_dir, SaveEntry, SaveFile, SaveMeta};
+
+use crate::command::PlayerId;
+use crate::empire::{Difficulty, Empire, EmpireSetup, Owner, PlayerControlled, Resources};
+use crate::fleet::{Fleet, LFleet, MoveOrder, Vessels};
+use crate::game_map::galaxy::gen::GalaxyGenParams;
+use crate::game_map::galaxy::{Galaxy, PrimaryGalaxy};
+use crate::game_map::planetary_system::PlanetarySystem;
+use crate::game_map::Coordinate;
+use crate::tick::GameTick;
+use crate::utils::{ObjectId, ObjectRef};
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
+
+/// The version of the save format, saves of another version are refused.
+pub const SAVE_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EmpireSave {
+    pub id: ObjectId,
+    pub setup: EmpireSetup,
+    pub player: Option<PlayerId>,
+    pub resources: Resources,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct FleetSave {
+    pub id: ObjectId,
+    pub owner: ObjectId,
+    pub transform: Transform,
+    pub vessels: Vec<ObjectId>,
+    pub target: Option<ObjectId>,
+}
+
+/// The real time spent playing the current game, in seconds.
+#[derive(Resource, Copy, Clone, Default, Debug, PartialEq)]
+pub struct PlayTime(pub f64);
+
+/// A snapshot of the game, taken between two ticks.
+#[derive(Resource, Clone, Debug, Serialize, Deserialize)]
+pub struct SaveGame {
+    pub version: u32,
+    /// the next tick to execute.
+    pub tick: GameTick,
+    pub params: GalaxyGenParams,
+    /// missing from older saves, which were all played at normal.
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    pub empires: Vec<EmpireSave>,
+    /// the owned planetary systems and their owner.
+    pub systems: Vec<(ObjectId, ObjectId)>,
+    pub fleets: Vec<FleetSave>,
+    /// the [PlayTime], missing from older saves.
+    #[serde(default)]
+    pub play_time: f64,
+}
+
+type EmpireData = (
+    &'static ObjectId,
+    &'static EmpireSetup,
+    Option<&'static PlayerControlled>,
+    &'static Resources,
+);
+
+type FleetData = (
+    &'static ObjectId,
+    &'static Transform,
+    &'static Owner,
+    &'static Vessels,
+    Option<&'static MoveOrder>,
+);
+
+/// The queries needed to take a [SaveGame].
+#[derive(SystemParam)]
+pub struct SaveQuery<'w, 's> {
+    tick: Res<'w, GameTick>,
+    difficulty: Res<'w, Difficulty>,
+    play_time: Res<'w, PlayTime>,
+    q_galaxy: Query<'w, 's, &'static GalaxyGenParams, With<PrimaryGalaxy>>,
+    q_empire: Query<'w, 's, EmpireData, With<Empire>>,
+    q_system: Query<'w, 's, (&'static ObjectId, &'static Owner), With<PlanetarySystem>>,
+    q_fleet: Query<'w, 's, FleetData, With<Fleet>>,
+}
+
+impl SaveQuery<'_, '_> {
+    /// Take a snapshot of the current game. Return `None` if there is no
+    /// game map.
+    pub fn save(&self) -> Option<SaveGame> {
+        let params = self.q_galaxy.get_single().ok()?.clone();
+
+        let empires = self
+            .q_empire
+            .iter()
+            .map(|(id, setup, player, resources)| EmpireSave {
+                id: *id,
+                setup: setup.clone(),
+                player: player.map(|p| p.0),
+                resources: *resources,
+            })
+            .collect();
+
+        let systems = self
+            .q_system
+            .iter()
+            .map(|(id, owner)| (*id, owner.0.object_id))
+            .collect();
+
+        let fleets = self
+            .q_fleet
+            .iter()
+            .map(|(id, transform, owner, vessels, order)| FleetSave {
+                id: *id,
+                owner: owner.0.object_id,
+                transform: *transform,
+                vessels: vessels.0.iter().map(|v| v.object_id).collect(),
+                target: order.map(|o| o.target.object_id),
+            })
+            .collect();
+
+        Some(SaveGame {
+            version: SAVE_VERSION,
+            tick: *self.tick,
+            params,
+            difficulty: *self.difficulty,
+            empires,
+            systems,
+            fleets,
+            play_time: self.play_time.0,
+        })
+    }
+}
+
+/// Spawn the saved objects into the generated map, and resume the clock
+/// from the saved tick.
+/// # Schedule
+/// Enter [crate::AppState::InGame], after [crate::tick::reset], if
+/// [SaveGame] exists.
+pub fn restore(
+    mut commands: Commands,
+    save: Res<SaveGame>,
+    mut tick: ResMut<GameTick>,
+    q_galaxy: Query<(Entity, &ObjectId), With<PrimaryGalaxy>>,
+    q_object: Query<(Entity, &ObjectId), Without<Galaxy>>,
+) {
+    commands.remove_resource::<SaveGame>();
+    *tick = save.tick;
+    commands.insert_resource(save.difficulty);
+    commands.insert_resource(PlayTime(save.play_time));
+
+    let Ok((galaxy, galaxy_id)) = q_galaxy.get_single() else {
+        error!("Fail to restore the save, the map is missing.");
+        return;
+    };
+    let galaxy = ObjectRef::new(galaxy, *galaxy_id);
+
+    let mut objects: HashMap<ObjectId, Entity> =
+        q_object.iter().map(|(entity, id)| (*id, entity)).collect();
+    let find = |objects: &HashMap<ObjectId, Entity>, id: ObjectId| {
+        ObjectRef::new(objects.get(&id).copied().unwrap_or(Entity::PLACEHOLDER), id)
+    };
+
+    for empire in save.empires.iter() {
+        let mut entity =
+            commands.spawn((Empire, empire.id, empire.setup.clone(), empire.resources));
+        if let Some(player) = empire.player {
+            entity.insert(PlayerControlled(player));
+        }
+        objects.insert(empire.id, entity.id());
+    }
+
+    for (system, owner) in save.systems.iter() {
+        match objects.get(system) {
+            Some(entity) => {
+                commands
+                    .entity(*entity)
+                    .insert(Owner(find(&objects, *owner)));
+            }
+            None => warn!("Saved system {:?} is not in the map", system),
+        }
+    }
+
+    // fleets first get an entity, so that they can refer to each other.
+    for fleet in save.fleets.iter() {
+        objects.insert(fleet.id, commands.spawn_empty().id());
+    }
+
+    for fleet in save.fleets.iter() {
+        let entity = objects[&fleet.id];
+        commands.entity(entity).insert(LFleet {
+            marker: Fleet,
+            id: fleet.id,
+            transform: fleet.transform,
+            vessels: Vessels(fleet.vessels.iter().map(|id| find(&objects, *id)).collect()),
+            owner: Owner(find(&objects, fleet.owner)),
+            coordinate: Coordinate::Galaxy(galaxy),
+        });
+        if let Some(target) = fleet.target {
+            commands.entity(entity).insert(MoveOrder {
+                target: find(&objects, target),
+            });
+        }
+    }
+
+    info!("Restored save at tick {}", save.tick.0);
+}
+
+/// Start counting the [PlayTime] of a new game.
+/// # Schedule
+/// Enter [crate::AppState::InGame], unless a save is restored.
+pub fn reset_play_time(mut play_time: ResMut<PlayTime>) {
+    *play_time = PlayTime::default();
+}
+
+/// Count the [PlayTime].
+/// # Schedule
+/// [Update], in [crate::AppState::InGame].
+pub fn count_play_time(mut play_time: ResMut<PlayTime>, time: Res<Time<Real>>) {
+    play_time.0 += time.delta_seconds_f64();
+}
@@ -0,0 +1,188 @@
+//! The simulation clock of the game.
+//!
+//! The game logic advances in discrete ticks in [FixedUpdate]. Each fixed
+//! step runs the [TickSet]s in order, but [TickSet::Simulation] only runs
+//! once [TickStep::ready] is set during [TickSet::Sync]. In a local game
+//! that happens on every step; in an online game the lockstep layer holds
+//! the tick back until the commands of all players have arrived.
+
+use crate::command::{PendingCommands, PlayerId, TickCommands};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// The number of ticks simulated per second of real time.
+pub const TICKS_PER_SECOND: f64 = 10.0;
+
+/// The number of ticks executed since the game started.
+#[derive(
+    Resource,
+    Copy,
+    Clone,
+    Default,
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub struct GameTick(pub u64);
+
+/// The number of ticks in an in-game day.
+pub const TICKS_PER_DAY: u64 = 10;
+
+/// The in-game year of the first tick.
+pub const START_YEAR: u64 = 2200;
+
+const DAYS_PER_MONTH: u64 = 30;
+const MONTHS_PER_YEAR: u64 = 12;
+
+/// The in-game date of a tick, on a calendar of twelve months of thirty
+/// days.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GameDate {
+    pub year: u64,
+    /// from 1 to 12.
+    pub month: u64,
+    /// from 1 to 30.
+    pub day: u64,
+}
+
+impl From<GameTick> for GameDate {
+    fn from(tick: GameTick) -> Self {
+        let days = tick.0 / TICKS_PER_DAY;
+        let months = days / DAYS_PER_MONTH;
+        Self {
+            year: START_YEAR + months / MONTHS_PER_YEAR,
+            month: months % MONTHS_PER_YEAR + 1,
+            day: days % DAYS_PER_MONTH + 1,
+        }
+    }
+}
+
+impl std::fmt::Display for GameDate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{:02}.{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// How fast the clock runs, picked by the player in a local game. An
+/// online game runs at the pace of the lockstep.
+#[derive(Resource, Copy, Clone, Default, Debug, PartialEq, Eq, Hash)]
+pub enum GameSpeed {
+    Paused,
+    #[default]
+    Normal,
+    Fast,
+    Fastest,
+}
+
+impl GameSpeed {
+    pub const ALL: [GameSpeed; 4] = [
+        GameSpeed::Paused,
+        GameSpeed::Normal,
+        GameSpeed::Fast,
+        GameSpeed::Fastest,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            GameSpeed::Paused => "Paused",
+            GameSpeed::Normal => "Normal",
+            GameSpeed::Fast => "Fast",
+            GameSpeed::Fastest => "Fastest",
+        }
+    }
+
+    /// The factor on [TICKS_PER_SECOND].
+    pub fn factor(&self) -> f64 {
+        match self {
+            GameSpeed::Paused => 0.0,
+            GameSpeed::Normal => 1.0,
+            GameSpeed::Fast => 2.0,
+            GameSpeed::Fastest => 4.0,
+        }
+    }
+}
+
+/// Whether the current fixed step is allowed to execute a tick.
+#[derive(Resource, Copy, Clone, Default, Debug)]
+pub struct TickStep {
+    pub ready: bool,
+}
+
+/// The stages of a tick, chained in [FixedUpdate].
+#[derive(SystemSet, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TickSet {
+    /// Decide if the tick can be executed, and collect its commands.
+    Sync,
+    /// The game logic. Only runs if [TickStep::ready] is set.
+    Simulation,
+    /// Move on to the next tick.
+    Advance,
+}
+
+/// Run condition for systems that should only run on an executed tick.
+pub fn tick_ready(step: Res<TickStep>) -> bool {
+    step.ready
+}
+
+/// Execute local commands immediately, used when no online session exists.
+/// # Schedule
+/// [FixedUpdate], in [TickSet::Sync].
+pub fn local_sync(
+    mut pending: ResMut<PendingCommands>,
+    mut tick_commands: ResMut<TickCommands>,
+    mut step: ResMut<TickStep>,
+) {
+    tick_commands.0 = pending
+        .0
+        .drain(..)
+        .map(|command| (PlayerId::default(), command))
+        .collect();
+    step.ready = true;
+}
+
+/// Increase [GameTick] after the tick is executed.
+/// # Schedule
+/// [FixedUpdate], in [TickSet::Advance].
+pub fn advance(
+    mut tick: ResMut<GameTick>,
+    mut step: ResMut<TickStep>,
+    mut tick_commands: ResMut<TickCommands>,
+) {
+    if step.ready {
+        tick.0 += 1;
+        step.ready = false;
+        tick_commands.0.clear();
+    }
+}
+
+/// Restart the clock for a new game.
+/// # Schedule
+/// Enter [crate::AppState::InGame].
+pub fn reset(mut tick: ResMut<GameTick>, mut step: ResMut<TickStep>) {
+    *tick = GameTick::default();
+    *step = TickStep::default();
+}
+
+/// Run the virtual clock, which drives [FixedUpdate], at the [GameSpeed].
+/// # Schedule
+/// [Update], when [GameSpeed] changes.
+pub fn apply_speed(speed: Res<GameSpeed>, mut time: ResMut<Time<Virtual>>) {
+    if *speed == GameSpeed::Paused {
+        time.pause();
+    } else {
+        time.unpause();
+        time.set_relative_speed_f64(speed.factor());
+    }
+}
+
+/// Run the clock at [GameSpeed::Normal], for a new game and for the menus.
+/// # Schedule
+/// Enter and exit [crate::AppState::InGame].
+pub fn reset_speed(mut speed: ResMut<GameSpeed>) {
+    speed.set_if_neq(GameSpeed::default());
+}
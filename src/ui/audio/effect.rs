@@ -0,0 +1,45 @@
+//! The sound effects of the game.
+
+use super::{play, AudioSettings, Channel};
+use bevy::prelude::*;
+
+/// Request to play a sound effect.
+#[derive(Event, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Effect {
+    /// Fleets were ordered to move.
+    MoveOrder,
+}
+
+/// The sources of each [Effect].
+#[derive(Resource, Clone, Debug)]
+pub struct Effects {
+    pub move_order: Handle<AudioSource>,
+}
+
+/// Load the [Effects].
+/// # Schedule
+/// [Startup]
+pub(super) fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Effects {
+        move_order: asset_server.load("audio/effects/move_order.ogg"),
+    });
+}
+
+/// Play the requested sound effects.
+/// # Schedule
+/// [Update], on [Effect].
+pub(super) fn play_requested(
+    mut commands: Commands,
+    mut er_effect: EventReader<Effect>,
+    effects: Res<Effects>,
+    sources: Res<Assets<AudioSource>>,
+    settings: Res<AudioSettings>,
+) {
+    let volume = settings.volume(Channel::Effects);
+    for effect in er_effect.read() {
+        let source = match effect {
+            Effect::MoveOrder => &effects.move_order,
+        };
+        play(&mut commands, &sources, source, volume);
+    }
+}
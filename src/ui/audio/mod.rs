@@ -0,0 +1,72 @@
+//! The music and sounds of the game.
+//!
+//! Each sound plays on a [Channel], at the volume of the channel times the
+//! master volume of the [AudioSettings]. Audio files that fail to load are
+//! reported and then skipped, so that the game runs silent without them.
+
+mod effect;
+mod music;
+mod ui_sound;
+
+pub use effect::Effect;
+pub use music::Playlist;
+pub use ui_sound::{click as click_sound, UiSound};
+
+use crate::game_map::gen::GenState;
+use crate::ui::settings::{AudioSettings, Channel};
+use crate::AppState;
+use bevy::asset::AssetLoadFailedEvent;
+use bevy::audio::Volume;
+use bevy::prelude::*;
+
+/// Report the audio files that failed to load.
+/// # Schedule
+/// [Update], on [AssetLoadFailedEvent].
+fn report_failed(mut er_failed: EventReader<AssetLoadFailedEvent<AudioSource>>) {
+    for event in er_failed.read() {
+        warn!(
+            "Fail to load audio {}, it stays silent: {}",
+            event.path, event.error
+        );
+    }
+}
+
+/// Play a sound once at `volume`. Sounds not loaded yet are skipped rather
+/// than played late.
+fn play(
+    commands: &mut Commands,
+    sources: &Assets<AudioSource>,
+    source: &Handle<AudioSource>,
+    volume: f32,
+) {
+    if volume <= 0.0 || !sources.contains(source) {
+        return;
+    }
+    commands.spawn(AudioBundle {
+        source: source.clone(),
+        settings: PlaybackSettings::DESPAWN.with_volume(Volume::new(volume)),
+    });
+}
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Playlist>()
+            .add_event::<UiSound>()
+            .add_event::<Effect>()
+            .add_systems(Startup, (ui_sound::setup, effect::setup))
+            .add_systems(OnEnter(AppState::InMenu), music::play_menu)
+            .add_systems(OnEnter(GenState::LoadAssets), music::preload_game)
+            .add_systems(OnEnter(AppState::InGame), music::play_game)
+            .add_systems(
+                Update,
+                (
+                    report_failed.run_if(on_event::<AssetLoadFailedEvent<AudioSource>>()),
+                    music::crossfade,
+                    ui_sound::play_requested.run_if(on_event::<UiSound>()),
+                    effect::play_requested.run_if(on_event::<Effect>()),
+                ),
+            );
+    }
+}
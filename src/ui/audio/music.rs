@@ -0,0 +1,219 @@
+//! The music playlists of the menu and the game.
+//!
+//! The tracks of a playlist play in order and loop. A track fades out
+//! while the next one fades in, either a little before it ends, or when
+//! the playlist changes with the [AppState].
+//!
+//! The tracks are intentionally not shipped in `assets/`, since no music
+//! with a suitable license has been picked yet. Missing tracks fail to load,
+//! which neither holds up the loading of the game nor stops the playlist;
+//! drop `.ogg` files at the paths below to hear them.
+
+use super::{AudioSettings, Channel};
+use crate::game_map::gen::LoadingAssets;
+use bevy::asset::LoadState;
+use bevy::audio::{PlaybackMode, Source, Volume};
+use bevy::prelude::*;
+
+/// The music of the menu.
+const MENU_TRACKS: [&str; 2] = ["audio/music/menu_0.ogg", "audio/music/menu_1.ogg"];
+
+/// The music of the game.
+const GAME_TRACKS: [&str; 3] = [
+    "audio/music/game_0.ogg",
+    "audio/music/game_1.ogg",
+    "audio/music/game_2.ogg",
+];
+
+/// The time for a track to fade in or out, in seconds.
+const CROSSFADE: f32 = 3.0;
+
+/// The tracks of the current playlist.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct Playlist {
+    tracks: Vec<Handle<AudioSource>>,
+    /// The index of the next track to play.
+    next: usize,
+}
+
+impl Playlist {
+    /// The next track to play, forgetting the tracks that failed to load.
+    fn next(&mut self, asset_server: &AssetServer) -> Option<Handle<AudioSource>> {
+        self.tracks
+            .retain(|track| !matches!(asset_server.load_state(track), LoadState::Failed(_)));
+        if self.tracks.is_empty() {
+            return None;
+        }
+        let track = self.tracks[self.next % self.tracks.len()].clone();
+        self.next = (self.next + 1) % self.tracks.len();
+        Some(track)
+    }
+}
+
+/// A music track being played.
+#[derive(Component, Clone, Default, Debug)]
+pub struct Track {
+    /// The fade level, from 0 when silent to 1.
+    level: f32,
+    fading_out: bool,
+    /// The time the track has been playing, in seconds.
+    elapsed: f32,
+    /// The length of the track, in seconds, if it is known.
+    duration: Option<f32>,
+    measured: bool,
+}
+
+/// Fade out the current tracks, and play the ones at `paths`.
+fn switch(
+    commands: &mut Commands,
+    playlist: &mut Playlist,
+    asset_server: &AssetServer,
+    q_track: &mut Query<&mut Track>,
+    paths: &[&'static str],
+) {
+    for mut track in q_track.iter_mut() {
+        track.fading_out = true;
+    }
+    *playlist = Playlist {
+        tracks: paths.iter().map(|path| asset_server.load(*path)).collect(),
+        next: 0,
+    };
+    if let Some(source) = playlist.next(asset_server) {
+        spawn_track(commands, source);
+    }
+}
+
+fn spawn_track(commands: &mut Commands, source: Handle<AudioSource>) {
+    commands.spawn((
+        AudioBundle {
+            source,
+            settings: PlaybackSettings {
+                mode: PlaybackMode::Once,
+                volume: Volume::new(0.0),
+                ..default()
+            },
+        },
+        Track::default(),
+        Name::new("Music Track"),
+    ));
+}
+
+/// Play the music of the menu.
+/// # Schedule
+/// Enter [crate::AppState::InMenu].
+pub(super) fn play_menu(
+    mut commands: Commands,
+    mut playlist: ResMut<Playlist>,
+    asset_server: Res<AssetServer>,
+    mut q_track: Query<&mut Track>,
+) {
+    switch(
+        &mut commands,
+        &mut playlist,
+        &asset_server,
+        &mut q_track,
+        &MENU_TRACKS,
+    );
+}
+
+/// Have the game wait for its music, so that it starts right away.
+/// # Schedule
+/// Enter [crate::game_map::gen::GenState::LoadAssets].
+pub(super) fn preload_game(asset_server: Res<AssetServer>, mut assets: ResMut<LoadingAssets>) {
+    let tracks = GAME_TRACKS
+        .iter()
+        .map(|path| asset_server.load::<AudioSource>(*path));
+    assets.0.extend(tracks.map(Handle::untyped));
+}
+
+/// Play the music of the game.
+/// # Schedule
+/// Enter [crate::AppState::InGame].
+pub(super) fn play_game(
+    mut commands: Commands,
+    mut playlist: ResMut<Playlist>,
+    asset_server: Res<AssetServer>,
+    mut q_track: Query<&mut Track>,
+) {
+    switch(
+        &mut commands,
+        &mut playlist,
+        &asset_server,
+        &mut q_track,
+        &GAME_TRACKS,
+    );
+}
+
+type TrackData = (
+    Entity,
+    &'static mut Track,
+    &'static Handle<AudioSource>,
+    Option<&'static AudioSink>,
+);
+
+/// Fade the tracks in and out, and start the next track when the current
+/// one is about to end.
+/// # Schedule
+/// [Update]
+pub(super) fn crossfade(
+    mut commands: Commands,
+    mut playlist: ResMut<Playlist>,
+    mut q_track: Query<TrackData>,
+    asset_server: Res<AssetServer>,
+    sources: Res<Assets<AudioSource>>,
+    settings: Res<AudioSettings>,
+    time: Res<Time<Real>>,
+) {
+    let delta = time.delta_seconds();
+    let volume = settings.volume(Channel::Music);
+    let mut playing = false;
+    let mut ending = false;
+
+    for (entity, mut track, source, sink) in q_track.iter_mut() {
+        let Some(sink) = sink else {
+            // still loading, unless it never will.
+            if matches!(asset_server.load_state(source), LoadState::Failed(_)) {
+                commands.entity(entity).despawn();
+                ending |= !track.fading_out;
+            } else {
+                playing |= !track.fading_out;
+            }
+            continue;
+        };
+
+        if !track.measured {
+            track.measured = true;
+            track.duration = sources
+                .get(source)
+                .and_then(|source| source.decoder().total_duration())
+                .map(|duration| duration.as_secs_f32());
+        }
+        track.elapsed += delta;
+
+        if track.fading_out {
+            track.level -= delta / CROSSFADE;
+            if track.level <= 0.0 {
+                commands.entity(entity).despawn();
+                continue;
+            }
+        } else {
+            track.level = (track.level + delta / CROSSFADE).min(1.0);
+            let near_end = track
+                .duration
+                .is_some_and(|duration| track.elapsed >= duration - CROSSFADE);
+            if sink.empty() || near_end {
+                track.fading_out = true;
+                ending = true;
+            } else {
+                playing = true;
+            }
+        }
+        sink.set_volume(track.level * volume);
+    }
+
+    if ending && !playing {
+        if let Some(source) = playlist.next(&asset_server) {
+            spawn_track(&mut commands, source);
+        }
+    }
+}
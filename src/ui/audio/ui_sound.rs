@@ -0,0 +1,53 @@
+//! The sounds of the menus.
+
+use super::{play, AudioSettings, Channel};
+use bevy::prelude::*;
+
+/// Request to play a sound of the UI.
+#[derive(Event, Copy, Clone, Debug, PartialEq, Eq)]
+pub enum UiSound {
+    Hover,
+    Click,
+}
+
+/// The sources of each [UiSound].
+#[derive(Resource, Clone, Debug)]
+pub struct UiSounds {
+    pub hover: Handle<AudioSource>,
+    pub click: Handle<AudioSource>,
+}
+
+/// Load the [UiSounds].
+/// # Schedule
+/// [Startup]
+pub(super) fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(UiSounds {
+        hover: asset_server.load("audio/ui/hover.ogg"),
+        click: asset_server.load("audio/ui/click.ogg"),
+    });
+}
+
+/// Play the requested sounds.
+/// # Schedule
+/// [Update], on [UiSound].
+pub(super) fn play_requested(
+    mut commands: Commands,
+    mut er_sound: EventReader<UiSound>,
+    sounds: Res<UiSounds>,
+    sources: Res<Assets<AudioSource>>,
+    settings: Res<AudioSettings>,
+) {
+    let volume = settings.volume(Channel::Ui);
+    for sound in er_sound.read() {
+        let source = match sound {
+            UiSound::Hover => &sounds.hover,
+            UiSound::Click => &sounds.click,
+        };
+        play(&mut commands, &sources, source, volume);
+    }
+}
+
+/// Play [UiSound::Click], meant to be piped into the action of a button.
+pub fn click(mut ew_sound: EventWriter<UiSound>) {
+    ew_sound.send(UiSound::Click);
+}
@@ -2,12 +2,14 @@
 //!
 //! # Motion Control
 //! - if the cursor is at the edge of the screen, the camera will move
-//!     parallel against z=z0 plane.
+//!     parallel against z=z0 plane, see [crate::ui::settings::EdgeScroll].
 //! - the mouse wheel scroll will move the camera along the local-z axis.
 //!     The constraint is based on the distance between the camera
 //!     and intersection point between camera local-z and z=0 plane.
 //! - if the middle button is hold, then the cursor will stop moving, camera
 //!     will rotate accordingly.
+//! - the same motions are bound to keys, see [Action].
+//! - in top-down mode, the wheel scales the visible area, and rotation is yaw only.
 //!
 //! # Constraint
 //! - height need to be between `-half_size.z` and `half_size.z + min(half_size.x, half_size.y) / tan θ`
@@ -16,48 +18,120 @@
 //! `half_size.x - (half_size.z - z) * tan θ`, where θ is the camera FOV.
 //! - if camera is in the `half_size`, then the rotation is not restricted
 //! - otherwise, the rotation is constrained by tan^(-1) ((half_size.x - x) / z).
+//!
+//! For the rotation, `half_size.x - x` is the distance to the border the
+//! camera looks toward, and z the height above the map.
+use super::top_down::{self, TopDown};
+use super::InputSettings;
 use super::PrimaryCamera;
-use crate::ui::input::{MouseMotion, MouseWheel};
+use crate::ui::input::{MouseButtons, MouseMotion, MouseWheel};
+use crate::ui::selection::Selection;
+use crate::ui::settings::{Action, EdgeScrollMode};
 use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+/// The largest angle between the camera and looking straight down, it
+/// must stay under a right angle for the camera to keep moving.
+const MAX_PITCH: f32 = 0.45 * std::f32::consts::PI;
+
+/// The rotation speed with keys, in rad/s.
+const KEY_ROTATE_SPEED: f32 = 1.5;
+
+/// The zoom speed with keys, in wheel scroll per second.
+const KEY_ZOOM_SPEED: f32 = 200.0;
 
 #[derive(Component, Copy, Clone, Default, Debug)]
 pub struct Controller {
     pub half_size: Vec3,
+    /// the center of the map, the constraints are relative to it.
+    pub center: Vec3,
+}
+
+impl Controller {
+    /// The area the camera can slide in at `translation`, so that it does
+    /// not look too far out of the map.
+    pub fn slide_area(&self, translation: Vec3, proj: &Projection) -> Rect {
+        let padding = match proj {
+            Projection::Perspective(p) => {
+                let h = (translation.z - self.center.z - self.half_size.z).max(0.0);
+                let v_padding = (0.5 * p.fov).tan() * h;
+                Vec2::new(v_padding * p.aspect_ratio, v_padding)
+            }
+            // the visible area does not depend on the height.
+            Projection::Orthographic(o) => o.area.half_size(),
+        };
+        let constraint = (self.half_size.xy() - padding).max(Vec2::ZERO);
+        Rect::from_center_half_size(self.center.xy(), constraint)
+    }
+
+    /// Slide the camera to look at `target`, keeping the height and the
+    /// rotation, within the [Controller::slide_area].
+    pub fn look_at(&self, transform: &mut Transform, proj: &Projection, target: Vec3) {
+        let forward = transform.forward();
+        let offset = if forward.z < -f32::EPSILON {
+            forward.xy() * (transform.translation.z - target.z) / -forward.z
+        } else {
+            Vec2::ZERO
+        };
+        let area = self.slide_area(transform.translation, proj);
+        let translation = (target.xy() - offset).clamp(area.min, area.max);
+        transform.translation.x = translation.x;
+        transform.translation.y = translation.y;
+    }
 }
 
 /// Control the [PrimaryCamera] horizontal movement (a.k.a slide).
 /// # Schedule
-/// [PostUpdate], we want to move it after all ray-cast
-/// computation are completed.
+/// [Update], in [crate::ui::camera::CameraSet::Motion].
 pub fn slide(
     mut q_camera: Query<(&mut Transform, &Projection, &Controller), With<PrimaryCamera>>,
     time: Res<Time<Real>>,
+    q_window: Query<&Window, With<PrimaryWindow>>,
     input: Res<MouseMotion>,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<InputSettings>,
+    mut scrolling: Local<f32>,
 ) {
     if let Some((mut transform, proj, ctrl)) = q_camera.get_single_mut().ok() {
-        let constraint = {
-            let h = (transform.translation.z - ctrl.half_size.z).max(0.0);
-            let padding = match proj {
-                Projection::Perspective(p) => {
-                    let v_theta = 0.5 * p.fov;
-                    let v_padding = v_theta.tan() * h;
-                    Vec2::new(v_padding * p.aspect_ratio, v_padding)
-                }
-                Projection::Orthographic(_) => {
-                    panic!("Unexpected camera projection, orthographic.")
-                }
-            };
-            (ctrl.half_size.xy() - padding).max(Vec2::ZERO)
+        let reach = match proj {
+            Projection::Perspective(_) => {
+                (transform.translation.z - ctrl.center.z - ctrl.half_size.z).max(0.0)
+            }
+            Projection::Orthographic(o) => o.area.height(),
         };
+        let area = ctrl.slide_area(transform.translation, proj);
+
+        let edge_scroll = &settings.edge_scroll;
+        let speed = edge_scroll.base_speed + reach * edge_scroll.height_factor;
 
-        let speed = 100.0 + (transform.translation.z - ctrl.half_size.z).max(0.0) * 1.0;
+        let enabled = match edge_scroll.mode {
+            EdgeScrollMode::Always => true,
+            EdgeScrollMode::WhenFocused => q_window.get_single().is_ok_and(|w| w.focused),
+            EdgeScrollMode::Never => false,
+        };
+        let mut edge = Vec2::ZERO;
+        if enabled && input.on_border != Vec2::ZERO {
+            *scrolling += time.delta_seconds();
+            let curve = |depth: f32| depth.signum() * edge_scroll.curve.apply(depth.abs());
+            edge = Vec2::new(curve(input.on_border.x), curve(input.on_border.y))
+                * edge_scroll.acceleration(*scrolling);
+        } else {
+            *scrolling = 0.0;
+        }
+
+        let bindings = &settings.bindings;
+        let pan = Vec2::new(
+            bindings.axis(Action::PanLeft, Action::PanRight, &keys),
+            bindings.axis(Action::PanUp, Action::PanDown, &keys),
+        );
+        let direction = (edge + pan).clamp(Vec2::NEG_ONE, Vec2::ONE);
 
         let mut delta = Vec2::ZERO;
-        delta += transform.local_x().xy().normalize() * input.on_border.x;
-        delta -= transform.local_y().xy().normalize() * input.on_border.y;
+        delta += transform.local_x().xy().normalize() * direction.x;
+        delta -= transform.local_y().xy().normalize() * direction.y;
         delta *= time.delta().as_secs_f32() * speed;
 
-        let new_translation = (transform.translation.xy() + delta).clamp(-constraint, constraint);
+        let new_translation = (transform.translation.xy() + delta).clamp(area.min, area.max);
 
         transform.translation.x = new_translation.x;
         transform.translation.y = new_translation.y;
@@ -66,32 +140,45 @@ pub fn slide(
 
 /// Control the [PrimaryCamera] zoom movement.
 /// # Schedule
-/// [PostUpdate], we want to move it after all ray-cast
-/// computation are completed.
+/// [Update], in [crate::ui::camera::CameraSet::Motion].
 pub fn zoom(
-    mut q_camera: Query<(&mut Transform, &Projection, &Controller), With<PrimaryCamera>>,
+    mut q_camera: Query<(&mut Transform, &mut Projection, &Controller), With<PrimaryCamera>>,
+    time: Res<Time<Real>>,
     input: Res<MouseWheel>,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<InputSettings>,
 ) {
-    if let Some((mut transform, proj, ctrl)) = q_camera.get_single_mut().ok() {
-        let constraint = {
-            let h = match proj {
-                Projection::Perspective(p) => {
-                    let v_theta = 0.5 * p.fov;
-                    f32::min(
-                        ctrl.half_size.y / v_theta.tan(),
-                        ctrl.half_size.x / (v_theta.tan() * p.aspect_ratio),
-                    )
-                }
-                Projection::Orthographic(_) => {
-                    panic!("Unexpected camera projection, orthographic.")
-                }
-            };
-
-            (-ctrl.half_size.z, ctrl.half_size.z + h * 0.8)
+    if let Some((mut transform, mut proj, ctrl)) = q_camera.get_single_mut().ok() {
+        let scroll = input.scroll.y * settings.mouse_scroll_sensitivity
+            + settings
+                .bindings
+                .axis(Action::ZoomOut, Action::ZoomIn, &keys)
+                * KEY_ZOOM_SPEED
+                * time.delta_seconds();
+
+        let h = match proj.as_mut() {
+            Projection::Perspective(p) => {
+                let v_theta = 0.5 * p.fov;
+                f32::min(
+                    ctrl.half_size.y / v_theta.tan(),
+                    ctrl.half_size.x / (v_theta.tan() * p.aspect_ratio),
+                )
+            }
+            Projection::Orthographic(o) => {
+                // scale the visible area instead of moving.
+                let aspect_ratio = o.area.width() / o.area.height();
+                let (min, max) = top_down::scale_range(ctrl.half_size, aspect_ratio);
+                o.scale = (o.scale * (-scroll * 0.01).exp()).clamp(min, max);
+                return;
+            }
         };
+        let constraint = (
+            ctrl.center.z - ctrl.half_size.z,
+            ctrl.center.z + ctrl.half_size.z + h * 0.8,
+        );
 
         let local_z = transform.local_z();
-        let delta = (-input.scroll.y).clamp(
+        let delta = (-scroll).clamp(
             (constraint.0 - transform.translation.z) / local_z.z,
             (constraint.1 - transform.translation.z) / local_z.z,
         );
@@ -99,50 +186,95 @@ pub fn zoom(
     }
 }
 
-// /// Control primary camera rotation.
-// /// # Schedule
-// /// `PostUpdate`, if `is_free_motion` is true.
-// pub fn rotate_main_camera(
-//     mut q_camera_origin: Query<&mut Transform, With<CameraOrigin>>,
-//     mut q_window: Query<&mut Window, With<PrimaryWindow>>,
-//     mut e_mouse_motion: EventReader<MouseMotion>,
-//     mut l_cursor_position: Local<Option<Vec2>>,
-//     r_mouse_button: Res<ButtonInput<MouseButton>>,
-//     r_motion_mode: Res<MotionMode>,
-//     r_settings: Res<InputSettings>,
-// ) {
-//     let max_θ = match *r_motion_mode {
-//         MotionMode::FreeMotion { max_θ, .. } => max_θ,
-//         other => panic!("Unexpected value {:?}", other),
-//     };
-
-//     let mut window = q_window.single_mut();
-
-//     if r_mouse_button.pressed(MouseButton::Middle) {
-//         // lock the cursor
-//         window.set_cursor_position(*l_cursor_position);
-
-//         // find mouse movement
-//         let mut delta = Vec2::ZERO;
-//         for e in e_mouse_motion.read() {
-//             delta += e.delta;
-//         }
-
-//         // apply sensitivity settings
-//         delta *= 0.01 * r_settings.mouse_motion_sensitivity;
-
-//         // rotate horizontally
-//         let mut transform = q_camera_origin.single_mut();
-//         transform.rotate_z(delta.x);
-
-//         // rotate vertically, apply constraint
-//         // TODO: optimize computation here, we can skip first and third
-//         // angle computation
-//         let (_, θ, _) = transform.rotation.to_euler(EulerRot::ZXY);
-//         delta.y = delta.y.clamp(-θ, max_θ - θ);
-
-//         transform.rotate_local_x(delta.y);
-//     } else {
-//         *l_cursor_position = window.cursor_position();
-//     }
-// }
+/// Control the [PrimaryCamera] rotation while the middle button is held,
+/// the pointer is locked in place meanwhile. Keys only rotate horizontally.
+/// # Schedule
+/// [Update], in [crate::ui::camera::CameraSet::Motion].
+pub fn rotate(
+    mut q_camera: Query<(&mut Transform, &Controller, Has<TopDown>), With<PrimaryCamera>>,
+    time: Res<Time<Real>>,
+    mut input: ResMut<MouseMotion>,
+    buttons: Res<MouseButtons>,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<InputSettings>,
+) {
+    let Ok((mut transform, ctrl, is_top_down)) = q_camera.get_single_mut() else {
+        input.locked = false;
+        return;
+    };
+    input.locked = buttons.middle.pressed();
+
+    let mut delta = Vec2::ZERO;
+    if input.locked {
+        // apply sensitivity settings
+        delta = input.motion * 0.01 * settings.mouse_motion_sensitivity;
+    }
+    delta.x += settings
+        .bindings
+        .axis(Action::RotateRight, Action::RotateLeft, &keys)
+        * KEY_ROTATE_SPEED
+        * time.delta_seconds();
+    if delta == Vec2::ZERO {
+        return;
+    }
+
+    // the rotation is a yaw around the world z axis, then a pitch away
+    // from looking straight down.
+    let (yaw, pitch, _) = transform.rotation.to_euler(EulerRot::ZXY);
+    let yaw = yaw + delta.x;
+
+    // rotate vertically, apply constraint
+    let offset = transform.translation - ctrl.center;
+    let h = offset.z - ctrl.half_size.z;
+    let max_pitch = if is_top_down {
+        0.0
+    } else if h > 0.0 {
+        // the distance to the border of the map, toward where it looks at.
+        let heading = Vec2::new(-yaw.sin(), yaw.cos());
+        let r = [0, 1]
+            .into_iter()
+            .filter(|&i| heading[i] != 0.0)
+            .map(|i| (ctrl.half_size[i] * heading[i].signum() - offset[i]) / heading[i])
+            .fold(f32::INFINITY, f32::min)
+            .max(0.0);
+        r.atan2(h).min(MAX_PITCH)
+    } else {
+        MAX_PITCH
+    };
+    let pitch = (pitch + delta.y).clamp(0.0, max_pitch);
+
+    transform.rotation = Quat::from_euler(EulerRot::ZXY, yaw, pitch, 0.0);
+}
+
+/// Move the [PrimaryCamera] to look at the middle of the [Selection].
+/// # Schedule
+/// [Update], in [crate::ui::camera::CameraSet::Motion].
+pub fn focus_selection(
+    mut q_camera: Query<(&mut Transform, &Projection, &Controller), With<PrimaryCamera>>,
+    q_target: Query<&GlobalTransform>,
+    selection: Res<Selection>,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<InputSettings>,
+) {
+    if !settings
+        .bindings
+        .just_pressed(Action::FocusSelection, &keys)
+    {
+        return;
+    }
+    let Ok((mut transform, proj, ctrl)) = q_camera.get_single_mut() else {
+        return;
+    };
+
+    let targets: Vec<Vec3> = selection
+        .0
+        .iter()
+        .filter_map(|object| q_target.get(object.entity).ok())
+        .map(|target| target.translation())
+        .collect();
+    if targets.is_empty() {
+        return;
+    }
+    let target = targets.iter().sum::<Vec3>() / targets.len() as f32;
+    ctrl.look_at(&mut transform, proj, target);
+}
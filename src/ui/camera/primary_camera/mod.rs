@@ -16,13 +16,23 @@
 //! The path should be a spline and the motion should be
 //! accelerate-constant-decelerate. It should also be possible to terminate
 //! during the motion and smoothly move back the initial position.
-use bevy::core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping};
+//!
+//! # Free Motion Mode
+//! The camera is moved by the player inside the constraints of the
+//! current map, see [free_motion]. It can also look at the map from the
+//! top with an orthographic projection, see [top_down].
 use bevy::prelude::*;
 mod free_motion;
+mod top_down;
+mod transition;
 pub use crate::ui::settings::InputSettings;
+use crate::ui::settings::VideoSettings;
 pub use free_motion::Controller as PrimCamFreeMotion;
+pub use top_down::TopDown;
+pub use transition::{GalaxyView, Transition, ViewChange, PLN_SYS_HALF_SIZE};
 
 use super::CameraSet;
+use crate::game_map::{Coordinate, MapReady};
 
 /// The marker component of primary camera.
 #[derive(Component, Copy, Clone, Default, Debug)]
@@ -31,29 +41,78 @@ pub struct PrimaryCamera;
 /// Spawn a new primary camera.
 /// # Schedule
 /// [PreStartup]
-pub fn setup(mut commands: Commands) {
-    commands.spawn((
+pub fn setup(mut commands: Commands, settings: Res<VideoSettings>) {
+    let mut camera = commands.spawn((
         Camera3dBundle {
             camera: Camera {
                 hdr: true,
                 ..default()
             },
-            tonemapping: Tonemapping::TonyMcMapface,
+            tonemapping: settings.tonemapper.tonemapping(),
             transform: Transform::from_xyz(0.0, 0.0, 50.0),
             ..default()
         },
         PrimaryCamera,
-        BloomSettings::NATURAL,
     ));
+    if let Some(bloom) = settings.bloom() {
+        camera.insert(bloom);
+    }
+}
+
+/// Move the [PrimaryCamera] into the map once it is ready.
+/// # Schedule
+/// [Update], on [MapReady].
+pub fn enter_map(
+    mut commands: Commands,
+    mut er_map_ready: EventReader<MapReady>,
+    mut q_camera: Query<
+        (Entity, &mut Transform, &mut Projection, Option<&TopDown>),
+        With<PrimaryCamera>,
+    >,
+) {
+    let Ok((camera, mut transform, mut proj, top_down)) = q_camera.get_single_mut() else {
+        return;
+    };
+    if let Some(top_down) = top_down {
+        *proj = top_down.restore(&mut transform);
+    }
+
+    for event in er_map_ready.read() {
+        commands
+            .entity(camera)
+            .remove::<(Transition, GalaxyView, TopDown)>()
+            .insert(Coordinate::Galaxy(event.galaxy))
+            .insert(PrimCamFreeMotion {
+                half_size: event.size.half_size,
+                ..default()
+            });
+    }
 }
 
 pub struct PrimaryCameraPlugin;
 
 impl Plugin for PrimaryCameraPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(PreStartup, setup).add_systems(
-            Update,
-            (free_motion::slide, free_motion::zoom).in_set(CameraSet::Motion),
-        );
+        app.add_event::<MapReady>()
+            .add_event::<ViewChange>()
+            .add_systems(PreStartup, setup)
+            .add_systems(Update, enter_map.run_if(on_event::<MapReady>()))
+            .add_systems(
+                Update,
+                (transition::enter_on_double_click, transition::back_on_key)
+                    .before(CameraSet::Motion),
+            )
+            .add_systems(
+                Update,
+                (
+                    top_down::toggle,
+                    free_motion::rotate,
+                    free_motion::slide,
+                    free_motion::zoom,
+                    free_motion::focus_selection,
+                    (transition::change_view, transition::advance).chain(),
+                )
+                    .in_set(CameraSet::Motion),
+            );
     }
 }
@@ -0,0 +1,93 @@
+//! Switch the camera between perspective and a flat, top-down view of the
+//! map with an orthographic projection.
+//!
+//! In top-down mode the camera stays just above the map and looks straight
+//! down, zooming scales the visible area. The perspective view is kept
+//! aside, and restored when leaving the mode.
+use super::free_motion::Controller;
+use super::{InputSettings, PrimaryCamera};
+use crate::ui::settings::Action;
+use bevy::prelude::*;
+use bevy::render::camera::ScalingMode;
+
+/// The [PrimaryCamera] is in top-down mode, this keeps the perspective view
+/// to restore.
+#[derive(Component, Clone, Debug)]
+pub struct TopDown {
+    perspective: PerspectiveProjection,
+    rotation: Quat,
+    height: f32,
+}
+
+impl TopDown {
+    /// Restore the perspective view into `transform`, above where the
+    /// top-down view currently is. Return the projection to use.
+    pub fn restore(&self, transform: &mut Transform) -> Projection {
+        transform.translation.z = self.height;
+        transform.rotation = self.rotation;
+        Projection::Perspective(self.perspective.clone())
+    }
+}
+
+/// The range of the orthographic scale for a map, the visible area is
+/// `2 * scale` high. At most, the whole map is visible.
+pub fn scale_range(half_size: Vec3, aspect_ratio: f32) -> (f32, f32) {
+    let max = f32::min(half_size.y, half_size.x / aspect_ratio);
+    (max * 0.01, max)
+}
+
+type CameraData = (
+    Entity,
+    &'static mut Transform,
+    &'static mut Projection,
+    &'static Controller,
+    Option<&'static TopDown>,
+);
+
+/// Switch between perspective and top-down view on [Action::ToggleTopDown].
+/// # Schedule
+/// [Update], in [crate::ui::camera::CameraSet::Motion].
+pub fn toggle(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<InputSettings>,
+    mut q_camera: Query<CameraData, With<PrimaryCamera>>,
+) {
+    if !settings.bindings.just_pressed(Action::ToggleTopDown, &keys) {
+        return;
+    }
+    let Ok((camera, mut transform, mut proj, ctrl, top_down)) = q_camera.get_single_mut() else {
+        return;
+    };
+
+    if let Some(top_down) = top_down {
+        *proj = top_down.restore(&mut transform);
+        commands.entity(camera).remove::<TopDown>();
+        return;
+    }
+    let Projection::Perspective(perspective) = proj.as_ref() else {
+        return;
+    };
+
+    // keep about the same area of the map plane visible.
+    let distance = (transform.translation.z - ctrl.center.z).max(0.0);
+    let (min, max) = scale_range(ctrl.half_size, perspective.aspect_ratio);
+    let scale = ((0.5 * perspective.fov).tan() * distance).clamp(min, max);
+
+    commands.entity(camera).insert(TopDown {
+        perspective: perspective.clone(),
+        rotation: transform.rotation,
+        height: transform.translation.z,
+    });
+
+    let (yaw, ..) = transform.rotation.to_euler(EulerRot::ZXY);
+    transform.rotation = Quat::from_rotation_z(yaw);
+    transform.translation.z = ctrl.center.z + ctrl.half_size.z + 1.0;
+    *proj = Projection::Orthographic(OrthographicProjection {
+        near: 0.0,
+        far: 2.0 * ctrl.half_size.z + 2.0,
+        scaling_mode: ScalingMode::FixedVertical(2.0),
+        scale,
+        ..default()
+    });
+}
@@ -0,0 +1,299 @@
+//! Control the camera when it is in transition mode, see the module
+//! docs of [super] for the expected motion.
+//!
+//! A transition is requested with a [ViewChange] event: double-clicking a
+//! planetary system enters it, and [Action::Back] goes back to the galaxy.
+//! While the camera moves, [Action::Back] turns it around to where it
+//! started.
+use super::free_motion::Controller;
+use super::top_down::TopDown;
+use super::{InputSettings, PrimaryCamera};
+use crate::game_map::planetary_system::PlanetarySystem;
+use crate::game_map::Coordinate;
+use crate::ui::input::MouseButtons;
+use crate::ui::settings::Action;
+use crate::utils::{ObjectId, ObjectRef};
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::{Click, Pointer, PointerButton};
+
+/// The time a whole transition takes, in seconds.
+const DURATION: f32 = 1.5;
+
+/// The part of a transition spent accelerating, and also the part spent
+/// decelerating.
+const ACCELERATION: f32 = 0.25;
+
+/// The half size of the local map of a planetary system.
+pub const PLN_SYS_HALF_SIZE: Vec3 = Vec3::new(4.0, 4.0, 1.0);
+
+/// A request to change what the [PrimaryCamera] looks at.
+#[derive(Event, Copy, Clone, Debug)]
+pub enum ViewChange {
+    /// Move into the local map of a planetary system.
+    PlnSys(ObjectRef),
+    /// Go back to the galaxy, or back to the start of the running
+    /// transition.
+    Back,
+}
+
+/// The galaxy view to return to, kept while the camera is in a
+/// planetary system.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct GalaxyView {
+    pub coordinate: Coordinate,
+    pub controller: Controller,
+}
+
+/// The [PrimaryCamera] is moving from one view to another. Free motion is
+/// disabled until it arrives.
+#[derive(Component, Clone, Debug)]
+pub struct Transition {
+    path: CubicCurve<Vec3>,
+    rotation: (Quat, Quat),
+    from: (Coordinate, Controller),
+    to: (Coordinate, Controller),
+    /// the time spent along the path, in seconds.
+    elapsed: f32,
+    reversed: bool,
+}
+
+impl Transition {
+    fn new(
+        start: &Transform,
+        end: &Transform,
+        from: (Coordinate, Controller),
+        to: (Coordinate, Controller),
+    ) -> Self {
+        // hover toward the destination while changing height, then
+        // settle vertically onto it.
+        let (p0, p3) = (start.translation, end.translation);
+        let p1 = p0.lerp(p3.xy().extend(p0.z), 0.5);
+        let p2 = p3.xy().extend(p3.z.lerp(p0.z, 0.5));
+
+        Self {
+            path: CubicBezier::new([[p0, p1, p2, p3]]).to_curve(),
+            rotation: (start.rotation, end.rotation),
+            from,
+            to,
+            elapsed: 0.0,
+            reversed: false,
+        }
+    }
+
+    /// Turn around, and smoothly move back to where it started.
+    pub fn reverse(&mut self) {
+        self.reversed = !self.reversed;
+    }
+}
+
+/// Accelerate, keep a constant speed, then decelerate. Map the part of
+/// the time elapsed to the part of the path travelled.
+fn ease(s: f32) -> f32 {
+    let a = ACCELERATION;
+    let speed = 1.0 / (1.0 - a);
+    if s < a {
+        0.5 * speed * s * s / a
+    } else if s < 1.0 - a {
+        0.5 * speed * a + speed * (s - a)
+    } else {
+        1.0 - 0.5 * speed * (1.0 - s).powi(2) / a
+    }
+}
+
+/// The height above a map from which all of it is visible.
+fn view_height(half_size: Vec3, proj: &Projection) -> f32 {
+    match proj {
+        Projection::Perspective(p) => {
+            let v_theta = 0.5 * p.fov;
+            f32::min(
+                half_size.y / v_theta.tan(),
+                half_size.x / (v_theta.tan() * p.aspect_ratio),
+            )
+        }
+        Projection::Orthographic(_) => half_size.max_element(),
+    }
+}
+
+/// Start transitions on [ViewChange] requests.
+/// # Schedule
+/// [Update], in [crate::ui::camera::CameraSet::Motion].
+#[allow(clippy::type_complexity)]
+pub fn change_view(
+    mut commands: Commands,
+    mut er_view: EventReader<ViewChange>,
+    mut q_camera: Query<
+        (
+            Entity,
+            &Transform,
+            &Projection,
+            Option<&Coordinate>,
+            Option<&Controller>,
+            Option<&mut Transition>,
+            Option<&GalaxyView>,
+            Option<&TopDown>,
+        ),
+        With<PrimaryCamera>,
+    >,
+    q_target: Query<&Transform, Without<PrimaryCamera>>,
+) {
+    let Ok((camera, transform, proj, coordinate, controller, mut transition, galaxy, top_down)) =
+        q_camera.get_single_mut()
+    else {
+        return;
+    };
+
+    // transitions always happen in perspective, start from the perspective
+    // view when in top-down mode.
+    let mut start = *transform;
+    let proj = match top_down {
+        Some(top_down) => top_down.restore(&mut start),
+        None => proj.clone(),
+    };
+    let proj = &proj;
+
+    for event in er_view.read() {
+        if let Some(transition) = transition.as_mut() {
+            if let ViewChange::Back = event {
+                transition.reverse();
+            }
+            continue;
+        }
+        let (Some(coordinate), Some(controller)) = (coordinate, controller) else {
+            continue;
+        };
+
+        let (to, end) = match *event {
+            ViewChange::PlnSys(system) => {
+                let Ok(target) = q_target.get(system.entity) else {
+                    continue;
+                };
+                if let Coordinate::Galaxy(_) = coordinate {
+                    commands.entity(camera).insert(GalaxyView {
+                        coordinate: *coordinate,
+                        controller: *controller,
+                    });
+                }
+
+                // at (0, 0, z) of the local map, looking at its center.
+                let center = target.translation;
+                let height = PLN_SYS_HALF_SIZE.z + view_height(PLN_SYS_HALF_SIZE, proj) * 0.8;
+                let end = Transform::from_translation(center + Vec3::Z * height)
+                    .looking_at(center, Vec3::Y);
+                let to = Controller {
+                    half_size: PLN_SYS_HALF_SIZE,
+                    center,
+                };
+                ((Coordinate::PlnSys(system), to), end)
+            }
+            ViewChange::Back => {
+                let (Coordinate::PlnSys(system), Some(galaxy)) = (coordinate, galaxy) else {
+                    continue;
+                };
+                let Ok(target) = q_target.get(system.entity) else {
+                    continue;
+                };
+
+                // above the planetary system, at a height based on the
+                // galaxy size, looking down at it.
+                let ctrl = galaxy.controller;
+                let height = ctrl.half_size.z + view_height(ctrl.half_size, proj) * 0.1;
+                let end = Transform::from_translation(
+                    target.translation.xy().extend(ctrl.center.z + height),
+                )
+                .looking_at(target.translation, Vec3::Y);
+                ((galaxy.coordinate, ctrl), end)
+            }
+        };
+
+        if top_down.is_some() {
+            commands
+                .entity(camera)
+                .remove::<TopDown>()
+                .insert(proj.clone());
+        }
+        commands
+            .entity(camera)
+            .remove::<Controller>()
+            .insert(Transition::new(
+                &start,
+                &end,
+                (*coordinate, *controller),
+                to,
+            ));
+        return;
+    }
+}
+
+/// Move the camera along the path of its [Transition], and give back
+/// free motion at either end.
+/// # Schedule
+/// [Update], in [crate::ui::camera::CameraSet::Motion].
+pub fn advance(
+    mut commands: Commands,
+    time: Res<Time<Real>>,
+    mut q_camera: Query<(Entity, &mut Transform, &mut Transition), With<PrimaryCamera>>,
+) {
+    let Ok((camera, mut transform, mut transition)) = q_camera.get_single_mut() else {
+        return;
+    };
+
+    let delta = time.delta_seconds();
+    let elapsed = if transition.reversed {
+        transition.elapsed - delta
+    } else {
+        transition.elapsed + delta
+    };
+    transition.elapsed = elapsed.clamp(0.0, DURATION);
+
+    let s = ease(transition.elapsed / DURATION);
+    transform.translation = transition.path.position(s);
+    transform.rotation = transition.rotation.0.slerp(transition.rotation.1, s);
+
+    let arrived = match transition.reversed {
+        false if transition.elapsed >= DURATION => Some(transition.to),
+        true if transition.elapsed <= 0.0 => Some(transition.from),
+        _ => None,
+    };
+    if let Some((coordinate, controller)) = arrived {
+        let mut camera = commands.entity(camera);
+        camera
+            .remove::<Transition>()
+            .insert(coordinate)
+            .insert(controller);
+        if let Coordinate::Galaxy(_) = coordinate {
+            camera.remove::<GalaxyView>();
+        }
+    }
+}
+
+/// Request to enter a planetary system when it is double-clicked.
+/// # Schedule
+/// [Update]
+pub fn enter_on_double_click(
+    mut er_click: EventReader<Pointer<Click>>,
+    mut ew_view: EventWriter<ViewChange>,
+    q_system: Query<&ObjectId, With<PlanetarySystem>>,
+    buttons: Res<MouseButtons>,
+) {
+    for click in er_click.read() {
+        if click.button != PointerButton::Primary || !buttons.left.double_clicked() {
+            continue;
+        }
+        if let Ok(id) = q_system.get(click.target) {
+            ew_view.send(ViewChange::PlnSys(ObjectRef::new(click.target, *id)));
+        }
+    }
+}
+
+/// Request to go back on [Action::Back].
+/// # Schedule
+/// [Update]
+pub fn back_on_key(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<InputSettings>,
+    mut ew_view: EventWriter<ViewChange>,
+) {
+    if settings.bindings.just_pressed(Action::Back, &keys) {
+        ew_view.send(ViewChange::Back);
+    }
+}
@@ -0,0 +1,353 @@
+//! Tooltips and the info panel of the objects of the map.
+//!
+//! The object under the pointer, from the `bevy_mod_picking` events, gets a
+//! tooltip with its name and key facts. The last selected object gets the
+//! info panel, with all of its facts.
+//!
+//! What is shown of an object comes from an [ObjectInfo], added with
+//! [InfoAppExt::add_object_info]. Each one is asked in turn, the first one
+//! describing the object wins. The tooltip and the panel are only rebuilt
+//! when what they show changes.
+
+use super::{fleet_name, system_name, UiConfigs};
+use crate::empire::{EmpireSetup, Owner};
+use crate::fleet::{Fleet, MoveOrder, Vessels};
+use crate::game_map::planetary_system::{PlanetarySystem, Planets, Star};
+use crate::ui::camera::PrimaryCamera;
+use crate::ui::input::MouseMotion;
+use crate::ui::selection::Selection;
+use crate::utils::ObjectId;
+use crate::AppState;
+use bevy::ecs::system::{StaticSystemParam, SystemParam, SystemParamItem};
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::{Out, Over, Pickable, Pointer};
+
+/// What the UI shows of an object.
+#[derive(Clone, Default, Debug, PartialEq)]
+pub struct Info {
+    pub title: String,
+    /// the facts, as a label and a value.
+    pub facts: Vec<(String, String)>,
+}
+
+/// Describes a kind of object for the tooltip and the info panel.
+pub trait ObjectInfo: Send + Sync + 'static {
+    /// The data read to describe the objects.
+    type Param: SystemParam + 'static;
+
+    /// Describe `entity`, `None` if it is not an object of this kind. Only
+    /// the key facts are asked for the tooltip, all of them if `detailed`
+    /// is set.
+    fn describe(
+        entity: Entity,
+        detailed: bool,
+        param: &SystemParamItem<Self::Param>,
+    ) -> Option<Info>;
+}
+
+/// The stages of the tooltip and the info panel, chained in [Update].
+#[derive(SystemSet, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum InfoSet {
+    /// Find the hovered and the selected objects.
+    Target,
+    /// Ask each [ObjectInfo] about them.
+    Describe,
+    /// Show what was described.
+    Show,
+}
+
+pub trait InfoAppExt {
+    /// Show the objects described by `T` in tooltips and the info panel.
+    fn add_object_info<T: ObjectInfo>(&mut self) -> &mut Self;
+}
+
+impl InfoAppExt for App {
+    fn add_object_info<T: ObjectInfo>(&mut self) -> &mut Self {
+        self.add_systems(Update, describe::<T>.in_set(InfoSet::Describe))
+    }
+}
+
+/// The objects to describe this frame, and their descriptions so far.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct InfoTargets {
+    hovered: Option<Entity>,
+    selected: Option<Entity>,
+    hover: Option<Info>,
+    panel: Option<Info>,
+}
+
+/// What the tooltip and the info panel show.
+#[derive(Resource, Clone, Default, Debug, PartialEq)]
+pub struct ShownInfo {
+    hover: Option<Info>,
+    panel: Option<Info>,
+}
+
+/// The tooltip, following the pointer.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct Tooltip;
+
+/// The info panel of the selected object.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct InfoPanel;
+
+/// Spawn the tooltip and the info panel, both hidden.
+/// # Schedule
+/// Enter [AppState::InGame].
+pub(super) fn setup(
+    mut commands: Commands,
+    mut targets: ResMut<InfoTargets>,
+    mut shown: ResMut<ShownInfo>,
+    ui_config: Res<UiConfigs>,
+    q_camera: Query<Entity, With<PrimaryCamera>>,
+) {
+    *targets = InfoTargets::default();
+    *shown = ShownInfo::default();
+    let Ok(camera) = q_camera.get_single() else {
+        return;
+    };
+
+    let padding = UiRect::all(Val::Px(8.0 * ui_config.scale));
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::Column,
+                padding,
+                ..default()
+            },
+            background_color: ui_config.menu.color_focus.into(),
+            z_index: ZIndex::Global(512),
+            ..default()
+        })
+        .insert(Tooltip)
+        .insert(Name::new("Tooltip"))
+        .insert(TargetCamera(camera))
+        .insert(StateScoped(AppState::InGame))
+        .insert(Pickable::IGNORE);
+
+    commands
+        .spawn(NodeBundle {
+            style: Style {
+                display: Display::None,
+                position_type: PositionType::Absolute,
+                left: Val::Px(8.0 * ui_config.scale),
+                bottom: Val::Px(8.0 * ui_config.scale),
+                width: Val::Px(280.0 * ui_config.scale),
+                flex_direction: FlexDirection::Column,
+                row_gap: Val::Px(4.0 * ui_config.scale),
+                padding,
+                ..default()
+            },
+            background_color: ui_config.menu.color_none.into(),
+            ..default()
+        })
+        .insert(InfoPanel)
+        .insert(Name::new("Info Panel"))
+        .insert(TargetCamera(camera))
+        .insert(StateScoped(AppState::InGame));
+}
+
+/// Follow the hovered object from the pointer events, and the last
+/// selected one.
+/// # Schedule
+/// [Update], in [InfoSet::Target].
+pub(super) fn target(
+    mut er_over: EventReader<Pointer<Over>>,
+    mut er_out: EventReader<Pointer<Out>>,
+    mut targets: ResMut<InfoTargets>,
+    selection: Res<Selection>,
+) {
+    for out in er_out.read() {
+        if targets.hovered == Some(out.target) {
+            targets.hovered = None;
+        }
+    }
+    if let Some(over) = er_over.read().last() {
+        targets.hovered = Some(over.target);
+    }
+    targets.selected = selection.0.last().map(|object| object.entity);
+    targets.hover = None;
+    targets.panel = None;
+}
+
+/// Describe the targets with `T`, unless described already.
+/// # Schedule
+/// [Update], in [InfoSet::Describe].
+fn describe<T: ObjectInfo>(mut targets: ResMut<InfoTargets>, param: StaticSystemParam<T::Param>) {
+    if let (None, Some(hovered)) = (&targets.hover, targets.hovered) {
+        targets.hover = T::describe(hovered, false, &param);
+    }
+    if let (None, Some(selected)) = (&targets.panel, targets.selected) {
+        targets.panel = T::describe(selected, true, &param);
+    }
+}
+
+/// Keep what was described, if it changed.
+/// # Schedule
+/// [Update], in [InfoSet::Show].
+pub(super) fn commit(mut targets: ResMut<InfoTargets>, mut shown: ResMut<ShownInfo>) {
+    shown.set_if_neq(ShownInfo {
+        hover: targets.hover.take(),
+        panel: targets.panel.take(),
+    });
+}
+
+/// Spawn the title and the facts of `info` into `parent`.
+fn spawn_info(parent: &mut ChildBuilder, ui_config: &UiConfigs, info: &Info) {
+    let text_style = TextStyle {
+        font: ui_config.text.font.clone(),
+        font_size: 16.0 * ui_config.scale,
+        color: ui_config.text.color,
+    };
+    let title_style = TextStyle {
+        font_size: 20.0 * ui_config.scale,
+        ..text_style.clone()
+    };
+
+    parent
+        .spawn(TextBundle::from_section(&info.title, title_style))
+        .insert(Pickable::IGNORE);
+    for (label, value) in info.facts.iter() {
+        parent
+            .spawn(TextBundle::from_section(
+                format!("{}: {}", label, value),
+                text_style.clone(),
+            ))
+            .insert(Pickable::IGNORE);
+    }
+}
+
+type TooltipNode = (With<Tooltip>, Without<InfoPanel>);
+
+/// Rebuild the tooltip and the info panel when what they show changes.
+/// # Schedule
+/// [Update], in [InfoSet::Show], after [commit].
+pub(super) fn rebuild(
+    mut commands: Commands,
+    shown: Res<ShownInfo>,
+    ui_config: Res<UiConfigs>,
+    mut q_tooltip: Query<(Entity, &mut Style), TooltipNode>,
+    mut q_panel: Query<(Entity, &mut Style), With<InfoPanel>>,
+) {
+    let nodes = [
+        (q_tooltip.get_single_mut(), &shown.hover),
+        (q_panel.get_single_mut(), &shown.panel),
+    ];
+    for (node, info) in nodes {
+        let Ok((entity, mut style)) = node else {
+            continue;
+        };
+        commands.entity(entity).despawn_descendants();
+        let Some(info) = info else {
+            style.display = Display::None;
+            continue;
+        };
+        style.display = Display::Flex;
+        commands
+            .entity(entity)
+            .with_children(|parent| spawn_info(parent, &ui_config, info));
+    }
+}
+
+/// Move the tooltip along with the pointer.
+/// # Schedule
+/// [Update], in [InfoSet::Show].
+pub(super) fn follow_pointer(
+    motion: Res<MouseMotion>,
+    ui_config: Res<UiConfigs>,
+    mut q_tooltip: Query<&mut Style, With<Tooltip>>,
+) {
+    let position = motion.position + 16.0 * ui_config.scale;
+    for mut style in q_tooltip.iter_mut() {
+        if style.left != Val::Px(position.x) || style.top != Val::Px(position.y) {
+            style.left = Val::Px(position.x);
+            style.top = Val::Px(position.y);
+        }
+    }
+}
+
+/// The name of the empire owning an object.
+fn owner_name(owner: Option<&Owner>, q_empire: &Query<&EmpireSetup>) -> String {
+    owner
+        .and_then(|owner| q_empire.get(owner.0.entity).ok())
+        .map_or("Unclaimed".to_string(), |setup| setup.name.clone())
+}
+
+type SystemData = (
+    &'static ObjectId,
+    &'static Star,
+    &'static Planets,
+    Option<&'static Owner>,
+);
+
+/// Describes planetary systems: their star, planets and owner.
+pub struct SystemInfo;
+
+impl ObjectInfo for SystemInfo {
+    type Param = (
+        Query<'static, 'static, SystemData, With<PlanetarySystem>>,
+        Query<'static, 'static, &'static EmpireSetup>,
+    );
+
+    fn describe(
+        entity: Entity,
+        detailed: bool,
+        (q_system, q_empire): &SystemParamItem<Self::Param>,
+    ) -> Option<Info> {
+        let (id, star, planets, owner) = q_system.get(entity).ok()?;
+        let mut facts = vec![
+            ("Owner".to_string(), owner_name(owner, q_empire)),
+            ("Star".to_string(), format!("{}-class", star.class())),
+        ];
+        if detailed {
+            facts[1].1 = format!("{}-class, {:.2} solar masses", star.class(), star.mass);
+            facts.push(("Planets".to_string(), planets.0.len().to_string()));
+        }
+        Some(Info {
+            title: system_name(id),
+            facts,
+        })
+    }
+}
+
+type FleetData = (
+    &'static ObjectId,
+    &'static Vessels,
+    &'static Owner,
+    Option<&'static MoveOrder>,
+);
+
+/// Describes fleets: their ships, owner and orders.
+pub struct FleetInfo;
+
+impl ObjectInfo for FleetInfo {
+    type Param = (
+        Query<'static, 'static, FleetData, With<Fleet>>,
+        Query<'static, 'static, &'static EmpireSetup>,
+    );
+
+    fn describe(
+        entity: Entity,
+        detailed: bool,
+        (q_fleet, q_empire): &SystemParamItem<Self::Param>,
+    ) -> Option<Info> {
+        let (id, vessels, owner, order) = q_fleet.get(entity).ok()?;
+        let orders = match order {
+            Some(order) => format!("Move to {}", system_name(&order.target.object_id)),
+            None => "None".to_string(),
+        };
+        let mut facts = vec![
+            ("Owner".to_string(), owner_name(Some(owner), q_empire)),
+            ("Orders".to_string(), orders),
+        ];
+        if detailed {
+            facts.insert(1, ("Ships".to_string(), vessels.0.len().to_string()));
+        }
+        Some(Info {
+            title: fleet_name(id),
+            facts,
+        })
+    }
+}
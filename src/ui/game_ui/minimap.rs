@@ -0,0 +1,296 @@
+//! The minimap, the whole galaxy in a corner of the screen.
+//!
+//! It shows the planetary systems in the color of the empire owning them,
+//! and the outline of the map area seen by the [PrimaryCamera]. Pressing or
+//! dragging on it moves the camera to look there, within the
+//! [PrimCamFreeMotion] constraints.
+//!
+//! The systems are drawn into a buffer only when they change, the view of
+//! the camera is drawn over a copy of it whenever the camera moves.
+
+use super::UiConfigs;
+use crate::empire::{EmpireSetup, Owner};
+use crate::game_map::planetary_system::PlanetarySystem;
+use crate::game_map::{Coordinate, MapReady};
+use crate::ui::camera::{PrimCamFreeMotion, PrimaryCamera};
+use crate::AppState;
+use bevy::color::palettes::tailwind;
+use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy_mod_picking::prelude::{Down, Drag, Pointer, PointerButton};
+
+/// The width and height of the image of the minimap, in pixels.
+const RESOLUTION: u32 = 256;
+
+/// The width and height of the minimap on the screen, in pixels at a UI
+/// scale of 1.
+const SIZE: f32 = 200.0;
+
+/// The minimap, showing the square of half size `extent` around the center
+/// of the galaxy.
+#[derive(Component, Clone, Debug)]
+pub struct Minimap {
+    extent: f32,
+    image: Handle<Image>,
+    /// the pixels of the planetary systems, without the view of the camera.
+    systems: Vec<u8>,
+}
+
+impl Minimap {
+    /// The pixel of a point of the map, from the top left corner.
+    fn pixel(&self, point: Vec2) -> Vec2 {
+        let uv = point / self.extent * Vec2::new(0.5, -0.5) + 0.5;
+        uv * RESOLUTION as f32
+    }
+
+    /// The point of the map at `uv`, from the top left corner of the minimap
+    /// to the bottom right one.
+    fn point(&self, uv: Vec2) -> Vec2 {
+        (uv - 0.5) * Vec2::new(2.0, -2.0) * self.extent
+    }
+}
+
+/// Set a pixel of an image of [RESOLUTION], if it is inside.
+fn put(buffer: &mut [u8], pixel: IVec2, color: [u8; 4]) {
+    let size = RESOLUTION as i32;
+    if (0..size).contains(&pixel.x) && (0..size).contains(&pixel.y) {
+        let i = (pixel.y * size + pixel.x) as usize * 4;
+        buffer[i..i + 4].copy_from_slice(&color);
+    }
+}
+
+/// Draw a line between two pixels.
+fn line(buffer: &mut [u8], from: Vec2, to: Vec2, color: [u8; 4]) {
+    let steps = (to - from).abs().max_element().ceil().max(1.0) as usize;
+    for i in 0..=steps {
+        let pixel = from.lerp(to, i as f32 / steps as f32);
+        put(buffer, pixel.floor().as_ivec2(), color);
+    }
+}
+
+/// Spawn the minimap once the map is ready.
+/// # Schedule
+/// [Update], on [MapReady].
+pub(super) fn setup(
+    mut commands: Commands,
+    mut er_map_ready: EventReader<MapReady>,
+    mut images: ResMut<Assets<Image>>,
+    ui_config: Res<UiConfigs>,
+    q_camera: Query<Entity, With<PrimaryCamera>>,
+    q_minimap: Query<Entity, With<Minimap>>,
+) {
+    let Some(event) = er_map_ready.read().last() else {
+        return;
+    };
+    let Ok(camera) = q_camera.get_single() else {
+        return;
+    };
+    for minimap in q_minimap.iter() {
+        commands.entity(minimap).despawn_recursive();
+    }
+
+    let image = Image::new_fill(
+        Extent3d {
+            width: RESOLUTION,
+            height: RESOLUTION,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::default(),
+    );
+    let systems = image.data.clone();
+    let image = images.add(image);
+
+    commands
+        .spawn(ImageBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                right: Val::Px(8.0 * ui_config.scale),
+                bottom: Val::Px(8.0 * ui_config.scale),
+                width: Val::Px(SIZE * ui_config.scale),
+                height: Val::Px(SIZE * ui_config.scale),
+                border: UiRect::all(Val::Px(1.0)),
+                ..default()
+            },
+            image: UiImage::new(image.clone()),
+            background_color: ui_config.menu.color_none.into(),
+            ..default()
+        })
+        .insert(BorderColor(ui_config.text.color))
+        .insert(Name::new("Minimap"))
+        .insert(TargetCamera(camera))
+        .insert(StateScoped(AppState::InGame))
+        .insert(Minimap {
+            extent: event.size.half_size.x.max(event.size.half_size.y),
+            image,
+            systems,
+        });
+}
+
+/// Follow the UI scale of the [UiConfigs].
+/// # Schedule
+/// [Update], in [AppState::InGame], when [UiConfigs] changes.
+pub(super) fn resize(ui_config: Res<UiConfigs>, mut q_minimap: Query<&mut Style, With<Minimap>>) {
+    for mut style in q_minimap.iter_mut() {
+        style.right = Val::Px(8.0 * ui_config.scale);
+        style.bottom = Val::Px(8.0 * ui_config.scale);
+        style.width = Val::Px(SIZE * ui_config.scale);
+        style.height = Val::Px(SIZE * ui_config.scale);
+    }
+}
+
+type ChangedSystem = (
+    With<PlanetarySystem>,
+    Or<(Changed<Owner>, Changed<Transform>)>,
+);
+
+/// Draw the planetary systems in the color of their owner, when they move
+/// or change hands.
+/// # Schedule
+/// [Update], in [AppState::InGame].
+pub(super) fn draw_systems(
+    mut q_minimap: Query<&mut Minimap>,
+    mut removed: RemovedComponents<Owner>,
+    q_changed: Query<(), ChangedSystem>,
+    q_system: Query<(&Transform, Option<&Owner>), With<PlanetarySystem>>,
+    q_empire: Query<&EmpireSetup>,
+) {
+    let removed = removed.read().count() > 0;
+    let Ok(mut minimap) = q_minimap.get_single_mut() else {
+        return;
+    };
+    if !minimap.is_added() && !removed && q_changed.is_empty() {
+        return;
+    }
+
+    let unowned = Color::from(tailwind::NEUTRAL_500).to_srgba().to_u8_array();
+    let mut systems = vec![0; minimap.systems.len()];
+    for (transform, owner) in q_system.iter() {
+        let color = owner
+            .and_then(|owner| q_empire.get(owner.0.entity).ok())
+            .map_or(unowned, |setup| setup.color().to_srgba().to_u8_array());
+        let pixel = minimap.pixel(transform.translation.xy()).floor().as_ivec2();
+        // a dot of 2x2 pixels, to be seen at any size.
+        for offset in [IVec2::ZERO, IVec2::X, IVec2::Y, IVec2::ONE] {
+            put(&mut systems, pixel + offset, color);
+        }
+    }
+    minimap.systems = systems;
+}
+
+/// The points of the map plane at the corners of the view of the camera.
+/// Corners above the horizon are cut at `reach` from the camera.
+fn footprint(camera: &Camera, transform: &GlobalTransform, reach: f32) -> Option<[Vec2; 4]> {
+    let rect = camera.logical_viewport_rect()?;
+    let corners = [
+        rect.min,
+        Vec2::new(rect.max.x, rect.min.y),
+        rect.max,
+        Vec2::new(rect.min.x, rect.max.y),
+    ];
+    let mut footprint = [Vec2::ZERO; 4];
+    for (point, corner) in footprint.iter_mut().zip(corners) {
+        let ray = camera.viewport_to_world(transform, corner)?;
+        let toward = match ray.intersect_plane(Vec3::ZERO, InfinitePlane3d::new(Vec3::Z)) {
+            Some(distance) => ray.direction.xy() * distance,
+            None => ray.direction.xy().normalize_or_zero() * reach,
+        };
+        *point = ray.origin.xy() + toward.clamp_length_max(reach);
+    }
+    Some(footprint)
+}
+
+type CameraView<'a> = (&'a Camera, &'a Transform, &'a Coordinate);
+
+/// Draw the view of the [PrimaryCamera] over the planetary systems, while
+/// it moves freely in the galaxy.
+/// # Schedule
+/// [Update], in [AppState::InGame], after the camera moves.
+pub(super) fn draw_view(
+    mut images: ResMut<Assets<Image>>,
+    mut last: Local<Option<[Vec2; 4]>>,
+    ui_config: Res<UiConfigs>,
+    q_minimap: Query<Ref<Minimap>>,
+    q_camera: Query<CameraView, (With<PrimaryCamera>, With<PrimCamFreeMotion>)>,
+) {
+    let Ok(minimap) = q_minimap.get_single() else {
+        return;
+    };
+    // the camera is a root entity, its transform is also the global one.
+    let view = q_camera
+        .get_single()
+        .ok()
+        .filter(|(_, _, coordinate)| matches!(coordinate, Coordinate::Galaxy(_)))
+        .and_then(|(camera, transform, _)| {
+            footprint(camera, &(*transform).into(), 2.0 * minimap.extent)
+        });
+    if !minimap.is_changed() && *last == view {
+        return;
+    }
+    *last = view;
+
+    let Some(image) = images.get_mut(&minimap.image) else {
+        return;
+    };
+    image.data.copy_from_slice(&minimap.systems);
+    let Some(view) = view else {
+        return;
+    };
+    let color = ui_config.text.color.to_srgba().to_u8_array();
+    for i in 0..view.len() {
+        let from = minimap.pixel(view[i]);
+        let to = minimap.pixel(view[(i + 1) % view.len()]);
+        line(&mut image.data, from, to, color);
+    }
+}
+
+type CameraMotion<'a> = (
+    &'a mut Transform,
+    &'a Projection,
+    &'a PrimCamFreeMotion,
+    &'a Coordinate,
+);
+
+/// Move the [PrimaryCamera] to look where the minimap is pressed or
+/// dragged on, if it looks at the galaxy.
+/// # Schedule
+/// [Update], in [AppState::InGame], before the camera moves.
+pub(super) fn jump(
+    mut er_down: EventReader<Pointer<Down>>,
+    mut er_drag: EventReader<Pointer<Drag>>,
+    q_minimap: Query<(&Minimap, &Node, &GlobalTransform)>,
+    mut q_camera: Query<CameraMotion, With<PrimaryCamera>>,
+) {
+    let downs = er_down
+        .read()
+        .filter(|down| down.button == PointerButton::Primary)
+        .map(|down| (down.target, down.pointer_location.position));
+    let drags = er_drag
+        .read()
+        .filter(|drag| drag.button == PointerButton::Primary)
+        .map(|drag| (drag.target, drag.pointer_location.position));
+    let Some((minimap, node, transform, position)) = downs
+        .chain(drags)
+        .filter_map(|(target, position)| {
+            let (minimap, node, transform) = q_minimap.get(target).ok()?;
+            Some((minimap, node, transform, position))
+        })
+        .last()
+    else {
+        return;
+    };
+    let Ok((mut camera, proj, ctrl, coordinate)) = q_camera.get_single_mut() else {
+        return;
+    };
+    if !matches!(coordinate, Coordinate::Galaxy(_)) {
+        return;
+    }
+
+    let rect = node.logical_rect(transform);
+    let uv = ((position - rect.min) / rect.size()).clamp(Vec2::ZERO, Vec2::ONE);
+    let target = minimap.point(uv).extend(ctrl.center.z);
+    ctrl.look_at(&mut camera, proj, target);
+}
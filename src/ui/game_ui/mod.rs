@@ -0,0 +1,120 @@
+//! The UI over the map during the game.
+
+pub mod info;
+mod minimap;
+mod outliner;
+mod top_bar;
+
+use super::camera::CameraSet;
+use super::menu_ui::UiConfigs;
+use crate::command::PlayerId;
+use crate::empire::{Empire, PlayerControlled};
+use crate::game_map::MapReady;
+use crate::net::NetSession;
+use crate::utils::ObjectId;
+use crate::AppState;
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use info::InfoAppExt;
+
+/// The empire of the local player.
+#[derive(SystemParam)]
+pub(super) struct LocalEmpire<'w, 's> {
+    session: Option<Res<'w, NetSession>>,
+    q_empire: Query<'w, 's, (Entity, &'static PlayerControlled), With<Empire>>,
+}
+
+impl LocalEmpire<'_, '_> {
+    pub fn get(&self) -> Option<Entity> {
+        let local = self
+            .session
+            .as_ref()
+            .map_or(PlayerId::default(), |session| session.local);
+        self.q_empire
+            .iter()
+            .find(|(_, player)| player.0 == local)
+            .map(|(entity, _)| entity)
+    }
+}
+
+/// The name of a planetary system shown to the player.
+fn system_name(id: &ObjectId) -> String {
+    format!("System {}", id.designation())
+}
+
+/// The name of a fleet shown to the player.
+fn fleet_name(id: &ObjectId) -> String {
+    format!("Fleet {}", id.designation())
+}
+
+pub struct InGamePlugin;
+
+impl Plugin for InGamePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, minimap::setup.run_if(on_event::<MapReady>()))
+            .add_systems(
+                Update,
+                (
+                    minimap::jump.before(CameraSet::Motion),
+                    (minimap::draw_systems, minimap::draw_view)
+                        .chain()
+                        .after(CameraSet::Motion),
+                    minimap::resize.run_if(resource_changed::<UiConfigs>),
+                )
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .init_resource::<outliner::OutlinerRows>()
+            .add_systems(OnEnter(AppState::InGame), outliner::setup)
+            .add_systems(
+                Update,
+                (
+                    (outliner::sync_rows, outliner::update_status).chain(),
+                    outliner::click_rows.before(CameraSet::Motion),
+                    outliner::highlight_rows,
+                )
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnEnter(AppState::InGame), top_bar::setup)
+            .add_systems(
+                Update,
+                (
+                    top_bar::build,
+                    (
+                        top_bar::update_stockpile,
+                        top_bar::update_date,
+                        top_bar::highlight_speed,
+                    ),
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .init_resource::<info::InfoTargets>()
+            .init_resource::<info::ShownInfo>()
+            .configure_sets(
+                Update,
+                (
+                    info::InfoSet::Target,
+                    info::InfoSet::Describe,
+                    info::InfoSet::Show,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnEnter(AppState::InGame), info::setup)
+            .add_systems(Update, info::target.in_set(info::InfoSet::Target))
+            .add_systems(
+                Update,
+                (
+                    (
+                        info::commit,
+                        info::rebuild.run_if(resource_changed::<info::ShownInfo>),
+                    )
+                        .chain(),
+                    info::follow_pointer,
+                )
+                    .in_set(info::InfoSet::Show),
+            )
+            .add_object_info::<info::SystemInfo>()
+            .add_object_info::<info::FleetInfo>();
+    }
+}
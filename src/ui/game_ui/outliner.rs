@@ -0,0 +1,400 @@
+//! The outliner, a foldable panel listing the fleets and the planetary
+//! systems of the player, grouped by [Category].
+//!
+//! Rows are added, moved between groups and removed as objects change
+//! hands, from change detection. The status of fleets is refreshed only
+//! when fleets move or get orders. A click on a row selects its object, a
+//! double click also moves the camera to it.
+
+use super::{fleet_name, system_name, top_bar, LocalEmpire, UiConfigs};
+use crate::empire::Owner;
+use crate::fleet::{Fleet, MoveOrder, FLEET_SPEED};
+use crate::game_map::planetary_system::{PlanetarySystem, Planets};
+use crate::game_map::Coordinate;
+use crate::ui::camera::{PrimCamFreeMotion, PrimaryCamera};
+use crate::ui::input::MouseButtons;
+use crate::ui::selection::{is_adding, Selection, SelectionChanged};
+use crate::utils::{ObjectId, ObjectRef};
+use crate::AppState;
+use bevy::color::palettes::tailwind;
+use bevy::prelude::*;
+use bevy::utils::HashMap;
+use bevy_mod_picking::prelude::{Click, Pickable, Pointer, PointerButton};
+use sickle_ui::prelude::*;
+
+/// Fleets of other empires closer than this, in ly, are fighting. Combat
+/// is not simulated yet, this only tells the player where it would be.
+const ENGAGE_RANGE: f32 = FLEET_SPEED;
+
+/// The groups of the outliner.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Category {
+    Fleets,
+    /// owned planetary systems with planets.
+    Colonies,
+    /// owned planetary systems without planets.
+    Outposts,
+}
+
+impl Category {
+    pub const ALL: [Category; 3] = [Category::Fleets, Category::Colonies, Category::Outposts];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Category::Fleets => "Fleets",
+            Category::Colonies => "Colonies",
+            Category::Outposts => "Outposts",
+        }
+    }
+}
+
+/// What a fleet is doing.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub enum FleetStatus {
+    #[default]
+    Idle,
+    Moving,
+    InCombat,
+}
+
+impl FleetStatus {
+    pub fn name(&self) -> &'static str {
+        match self {
+            FleetStatus::Idle => "Idle",
+            FleetStatus::Moving => "Moving",
+            FleetStatus::InCombat => "In Combat",
+        }
+    }
+
+    fn color(&self, ui_config: &UiConfigs) -> Color {
+        match self {
+            FleetStatus::Idle => ui_config.text.color,
+            FleetStatus::Moving => tailwind::SKY_400.into(),
+            FleetStatus::InCombat => tailwind::RED_500.into(),
+        }
+    }
+}
+
+/// The container of the rows of a [Category].
+#[derive(Component, Copy, Clone, Debug)]
+pub struct OutlinerGroup(Category);
+
+/// A row of the outliner, showing `object`.
+#[derive(Component, Copy, Clone, Debug)]
+pub struct OutlinerRow {
+    object: ObjectRef,
+}
+
+/// The text of the [FleetStatus] of a row.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct RowStatus;
+
+#[derive(Copy, Clone, Debug)]
+struct Row {
+    category: Category,
+    entity: Entity,
+    /// the [RowStatus] text, for fleets.
+    status: Option<Entity>,
+}
+
+/// The rows of the outliner, by the entity of their object.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct OutlinerRows(HashMap<Entity, Row>);
+
+/// Spawn the outliner, with an empty group for each [Category].
+/// # Schedule
+/// Enter [AppState::InGame].
+pub(super) fn setup(
+    mut commands: Commands,
+    mut rows: ResMut<OutlinerRows>,
+    ui_config: Res<UiConfigs>,
+    q_camera: Query<Entity, With<PrimaryCamera>>,
+) {
+    rows.0.clear();
+    let Ok(camera) = q_camera.get_single() else {
+        return;
+    };
+
+    commands
+        .ui_builder(UiRoot)
+        .column(|column| {
+            column.foldable("Outliner", true, false, |panel| {
+                for category in Category::ALL {
+                    panel.foldable(category.name(), true, false, |group| {
+                        group
+                            .column(|_| {})
+                            .insert(OutlinerGroup(category))
+                            .style()
+                            .width(Val::Percent(100.0));
+                    });
+                }
+            });
+        })
+        .insert(Name::new("Outliner"))
+        .insert(TargetCamera(camera))
+        .insert(StateScoped(AppState::InGame))
+        .style()
+        .position_type(PositionType::Absolute)
+        .left(Val::Px(8.0 * ui_config.scale))
+        .top(Val::Px((top_bar::HEIGHT + 8.0) * ui_config.scale))
+        .width(Val::Px(280.0 * ui_config.scale))
+        .background_color(ui_config.menu.color_none);
+}
+
+/// Spawn a row for `object` in `group`.
+fn spawn_row(
+    commands: &mut Commands,
+    ui_config: &UiConfigs,
+    group: Entity,
+    object: ObjectRef,
+    category: Category,
+) -> Row {
+    let text_style = TextStyle {
+        font: ui_config.text.font.clone(),
+        font_size: 16.0 * ui_config.scale,
+        color: ui_config.text.color,
+    };
+    let name = match category {
+        Category::Fleets => fleet_name(&object.object_id),
+        Category::Colonies | Category::Outposts => system_name(&object.object_id),
+    };
+
+    let mut status = None;
+    let entity = commands
+        .spawn(NodeBundle {
+            style: Style {
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::SpaceBetween,
+                padding: UiRect::axes(Val::Px(8.0 * ui_config.scale), Val::Px(2.0)),
+                ..default()
+            },
+            ..default()
+        })
+        .insert(OutlinerRow { object })
+        .with_children(|row| {
+            row.spawn(TextBundle::from_section(name, text_style.clone()))
+                .insert(Pickable::IGNORE);
+            if category == Category::Fleets {
+                let text = row
+                    .spawn(TextBundle::from_section("", text_style.clone()))
+                    .insert(RowStatus)
+                    .insert(Pickable::IGNORE)
+                    .id();
+                status = Some(text);
+            }
+        })
+        .id();
+    commands.entity(group).add_child(entity);
+    Row {
+        category,
+        entity,
+        status,
+    }
+}
+
+type SystemRow<'a> = (
+    Entity,
+    &'a ObjectId,
+    Option<Ref<'a, Owner>>,
+    Ref<'a, Planets>,
+);
+
+/// Add, move and remove the rows of objects that changed hands, or all of
+/// them when the outliner is spawned or the player changes empire.
+/// # Schedule
+/// [Update], in [AppState::InGame].
+#[allow(clippy::too_many_arguments)]
+pub(super) fn sync_rows(
+    mut commands: Commands,
+    mut rows: ResMut<OutlinerRows>,
+    mut removed_owner: RemovedComponents<Owner>,
+    mut removed_fleet: RemovedComponents<Fleet>,
+    mut last_empire: Local<Option<Entity>>,
+    ui_config: Res<UiConfigs>,
+    local: LocalEmpire,
+    q_group: Query<(Entity, Ref<OutlinerGroup>)>,
+    q_fleet: Query<(Entity, &ObjectId, Ref<Owner>), With<Fleet>>,
+    q_system: Query<SystemRow, With<PlanetarySystem>>,
+) {
+    let groups: HashMap<Category, Entity> = q_group
+        .iter()
+        .map(|(entity, group)| (group.0, entity))
+        .collect();
+    if groups.is_empty() {
+        return;
+    }
+    let empire = local.get();
+    let rebuild = q_group.iter().any(|(_, group)| group.is_added()) || *last_empire != empire;
+    *last_empire = empire;
+
+    let is_local = |owner: &Owner| Some(owner.0.entity) == empire;
+    let mut dirty: Vec<Entity> = removed_owner.read().chain(removed_fleet.read()).collect();
+    for (entity, _, owner) in q_fleet.iter() {
+        if rebuild || owner.is_changed() {
+            dirty.push(entity);
+        }
+    }
+    for (entity, _, owner, planets) in q_system.iter() {
+        let changed = owner.as_ref().is_some_and(|owner| owner.is_changed());
+        if rebuild || changed || planets.is_changed() {
+            dirty.push(entity);
+        }
+    }
+    if rebuild {
+        for (_, row) in rows.0.drain() {
+            commands.entity(row.entity).despawn_recursive();
+        }
+    }
+
+    for entity in dirty {
+        let placed = if let Ok((_, id, owner)) = q_fleet.get(entity) {
+            is_local(&owner).then_some((*id, Category::Fleets))
+        } else if let Ok((_, id, Some(owner), planets)) = q_system.get(entity) {
+            let category = if planets.0.is_empty() {
+                Category::Outposts
+            } else {
+                Category::Colonies
+            };
+            is_local(&owner).then_some((*id, category))
+        } else {
+            None
+        };
+
+        match (rows.0.get(&entity).copied(), placed) {
+            (Some(row), Some((_, category))) if row.category == category => {}
+            (row, placed) => {
+                if let Some(row) = row {
+                    commands.entity(row.entity).despawn_recursive();
+                    rows.0.remove(&entity);
+                }
+                if let Some((id, category)) = placed {
+                    let object = ObjectRef::new(entity, id);
+                    let row = spawn_row(
+                        &mut commands,
+                        &ui_config,
+                        groups[&category],
+                        object,
+                        category,
+                    );
+                    rows.0.insert(entity, row);
+                }
+            }
+        }
+    }
+}
+
+type ChangedFleet = (With<Fleet>, Or<(Changed<Transform>, Added<MoveOrder>)>);
+
+/// Show the [FleetStatus] of the fleets in the outliner, when fleets move,
+/// get orders or join the outliner.
+/// # Schedule
+/// [Update], in [AppState::InGame], after [sync_rows].
+pub(super) fn update_status(
+    mut removed: RemovedComponents<MoveOrder>,
+    mut q_status: Query<&mut Text, With<RowStatus>>,
+    rows: Res<OutlinerRows>,
+    ui_config: Res<UiConfigs>,
+    q_changed: Query<(), ChangedFleet>,
+    q_fleet: Query<(&Transform, &Owner, &Coordinate, Has<MoveOrder>), With<Fleet>>,
+) {
+    let removed = removed.read().count() > 0;
+    if !rows.is_changed() && !removed && q_changed.is_empty() {
+        return;
+    }
+
+    for (fleet, row) in rows.0.iter() {
+        let Some(Ok(mut text)) = row.status.map(|status| q_status.get_mut(status)) else {
+            continue;
+        };
+        let Ok((transform, owner, coordinate, moving)) = q_fleet.get(*fleet) else {
+            continue;
+        };
+        let in_combat = q_fleet
+            .iter()
+            .any(|(other, other_owner, other_coordinate, _)| {
+                other_owner.0.entity != owner.0.entity
+                    && other_coordinate == coordinate
+                    && other.translation.distance(transform.translation) <= ENGAGE_RANGE
+            });
+        let status = match (in_combat, moving) {
+            (true, _) => FleetStatus::InCombat,
+            (false, true) => FleetStatus::Moving,
+            (false, false) => FleetStatus::Idle,
+        };
+
+        // only touch the text when it changes, to skip its layout.
+        if text.sections[0].value != status.name() {
+            text.sections[0].value = status.name().to_string();
+            text.sections[0].style.color = status.color(&ui_config);
+        }
+    }
+}
+
+type CameraMotion<'a> = (
+    &'a mut Transform,
+    &'a Projection,
+    &'a PrimCamFreeMotion,
+    &'a Coordinate,
+);
+
+/// Select the object of a clicked row, and move the [PrimaryCamera] to it
+/// on a double click.
+/// # Schedule
+/// [Update], in [AppState::InGame].
+#[allow(clippy::too_many_arguments)]
+pub(super) fn click_rows(
+    mut er_click: EventReader<Pointer<Click>>,
+    mut ew_changed: EventWriter<SelectionChanged>,
+    mut selection: ResMut<Selection>,
+    buttons: Res<MouseButtons>,
+    keys: Res<ButtonInput<KeyCode>>,
+    q_row: Query<&OutlinerRow>,
+    q_target: Query<&GlobalTransform>,
+    mut q_camera: Query<CameraMotion, With<PrimaryCamera>>,
+) {
+    for click in er_click.read() {
+        if click.button != PointerButton::Primary {
+            continue;
+        }
+        let Ok(row) = q_row.get(click.target) else {
+            continue;
+        };
+        if selection.select([row.object], is_adding(&keys)) {
+            ew_changed.send(SelectionChanged);
+        }
+
+        if !buttons.left.double_clicked() {
+            continue;
+        }
+        let (Ok(target), Ok((mut transform, proj, ctrl, coordinate))) =
+            (q_target.get(row.object.entity), q_camera.get_single_mut())
+        else {
+            continue;
+        };
+        if matches!(coordinate, Coordinate::Galaxy(_)) {
+            ctrl.look_at(&mut transform, proj, target.translation());
+        }
+    }
+}
+
+/// Highlight the rows of the selected objects.
+/// # Schedule
+/// [Update], in [AppState::InGame], when the [Selection] or the rows change.
+pub(super) fn highlight_rows(
+    selection: Res<Selection>,
+    ui_config: Res<UiConfigs>,
+    q_added: Query<(), Added<OutlinerRow>>,
+    mut q_row: Query<(&OutlinerRow, &mut BackgroundColor)>,
+) {
+    if !selection.is_changed() && q_added.is_empty() {
+        return;
+    }
+    for (row, mut background) in q_row.iter_mut() {
+        let color = if selection.contains(row.object) {
+            ui_config.menu.color_focus
+        } else {
+            Color::NONE
+        };
+        background.set_if_neq(BackgroundColor(color));
+    }
+}
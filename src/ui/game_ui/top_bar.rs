@@ -0,0 +1,293 @@
+//! The top bar, with the stockpile of the player, the in-game date and the
+//! speed of the clock.
+//!
+//! The bar is rebuilt when the [UiConfigs] change, its values are written
+//! only when they change. Hovering the stockpile shows the income per tick
+//! by source. The speed buttons are only shown in a local game, an online
+//! game runs at the pace of the lockstep.
+
+use super::{LocalEmpire, UiConfigs};
+use crate::empire::{count_systems, Difficulty, Income, Owner, Resources};
+use crate::game_map::planetary_system::PlanetarySystem;
+use crate::net::NetSession;
+use crate::tick::{GameDate, GameSpeed, GameTick};
+use crate::ui::audio::click_sound;
+use crate::ui::camera::PrimaryCamera;
+use crate::AppState;
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::{Click, On, Out, Over, Pickable, Pointer};
+
+/// The height of the bar, in pixels at a UI scale of 1.
+pub const HEIGHT: f32 = 32.0;
+
+/// The top bar, its content is rebuilt when the [UiConfigs] change.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct TopBar;
+
+/// The text of the energy stockpile and income.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct EnergyText;
+
+/// The tooltip of the income by source, shown while the stockpile is
+/// hovered.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct IncomeTooltip;
+
+/// The text of the [IncomeTooltip].
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct IncomeText;
+
+/// The text of the in-game date.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct DateText;
+
+/// The button setting the clock to a [GameSpeed].
+#[derive(Component, Copy, Clone, Debug)]
+pub struct SpeedButton(GameSpeed);
+
+/// The label of the button of a [GameSpeed].
+fn speed_label(speed: GameSpeed) -> &'static str {
+    match speed {
+        GameSpeed::Paused => "||",
+        GameSpeed::Normal => ">",
+        GameSpeed::Fast => ">>",
+        GameSpeed::Fastest => ">>>",
+    }
+}
+
+/// Write `value` into `text`, if it is different.
+fn set_text(text: &mut Mut<Text>, value: String) {
+    if text.sections[0].value != value {
+        text.sections[0].value = value;
+    }
+}
+
+/// Spawn the top bar.
+/// # Schedule
+/// Enter [AppState::InGame].
+pub(super) fn setup(mut commands: Commands, q_camera: Query<Entity, With<PrimaryCamera>>) {
+    let Ok(camera) = q_camera.get_single() else {
+        return;
+    };
+    commands
+        .spawn(NodeBundle::default())
+        .insert(TopBar)
+        .insert(Name::new("Top Bar"))
+        .insert(TargetCamera(camera))
+        .insert(StateScoped(AppState::InGame));
+}
+
+/// Build the content of the top bar, when it is spawned or the [UiConfigs]
+/// change.
+/// # Schedule
+/// [Update], in [AppState::InGame].
+pub(super) fn build(
+    mut commands: Commands,
+    ui_config: Res<UiConfigs>,
+    session: Option<Res<NetSession>>,
+    q_bar: Query<(Entity, Ref<TopBar>)>,
+) {
+    let Ok((bar, marker)) = q_bar.get_single() else {
+        return;
+    };
+    if !marker.is_added() && !ui_config.is_changed() {
+        return;
+    }
+
+    let scale = ui_config.scale;
+    let text_style = TextStyle {
+        font: ui_config.text.font.clone(),
+        font_size: 16.0 * scale,
+        color: ui_config.text.color,
+    };
+    let text = |value: &str| TextBundle::from_section(value, text_style.clone());
+
+    commands
+        .entity(bar)
+        .despawn_descendants()
+        .insert(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                top: Val::Px(0.0),
+                left: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                height: Val::Px(HEIGHT * scale),
+                justify_content: JustifyContent::SpaceBetween,
+                align_items: AlignItems::Center,
+                padding: UiRect::horizontal(Val::Px(8.0 * scale)),
+                ..default()
+            },
+            background_color: ui_config.menu.color_none.into(),
+            ..default()
+        });
+
+    commands.entity(bar).with_children(|parent| {
+        parent
+            .spawn(NodeBundle::default())
+            .insert(On::<Pointer<Over>>::run(
+                |mut q_tooltip: Query<&mut Style, With<IncomeTooltip>>| {
+                    for mut style in q_tooltip.iter_mut() {
+                        style.display = Display::Flex;
+                    }
+                },
+            ))
+            .insert(On::<Pointer<Out>>::run(
+                |mut q_tooltip: Query<&mut Style, With<IncomeTooltip>>| {
+                    for mut style in q_tooltip.iter_mut() {
+                        style.display = Display::None;
+                    }
+                },
+            ))
+            .with_children(|stockpile| {
+                stockpile
+                    .spawn(text(""))
+                    .insert(EnergyText)
+                    .insert(Pickable::IGNORE);
+                stockpile
+                    .spawn(NodeBundle {
+                        style: Style {
+                            display: Display::None,
+                            position_type: PositionType::Absolute,
+                            top: Val::Percent(100.0),
+                            padding: UiRect::all(Val::Px(8.0 * scale)),
+                            ..default()
+                        },
+                        background_color: ui_config.menu.color_focus.into(),
+                        z_index: ZIndex::Global(16),
+                        ..default()
+                    })
+                    .insert(IncomeTooltip)
+                    .insert(Pickable::IGNORE)
+                    .with_children(|tooltip| {
+                        tooltip
+                            .spawn(text(""))
+                            .insert(IncomeText)
+                            .insert(Pickable::IGNORE);
+                    });
+            });
+
+        parent
+            .spawn(text(""))
+            .insert(DateText)
+            .insert(Pickable::IGNORE);
+
+        parent
+            .spawn(NodeBundle {
+                style: Style {
+                    column_gap: Val::Px(4.0 * scale),
+                    ..default()
+                },
+                ..default()
+            })
+            .with_children(|speeds| {
+                // an online game runs at the pace of the lockstep.
+                if session.is_some() {
+                    return;
+                }
+                for speed in GameSpeed::ALL {
+                    speeds
+                        .spawn(ButtonBundle {
+                            style: Style {
+                                min_width: Val::Px(32.0 * scale),
+                                justify_content: JustifyContent::Center,
+                                padding: UiRect::axes(Val::Px(4.0 * scale), Val::Px(2.0)),
+                                border: UiRect::all(Val::Px(1.0)),
+                                ..default()
+                            },
+                            border_color: ui_config.text.color.into(),
+                            ..default()
+                        })
+                        .insert(SpeedButton(speed))
+                        .insert(On::<Pointer<Click>>::run(click_sound.pipe(
+                            move |mut game_speed: ResMut<GameSpeed>| {
+                                game_speed.set_if_neq(speed);
+                            },
+                        )))
+                        .with_children(|button| {
+                            button
+                                .spawn(text(speed_label(speed)))
+                                .insert(Pickable::IGNORE);
+                        });
+                }
+            });
+    });
+}
+
+/// Show the energy of the player and its income by source, when they
+/// change.
+/// # Schedule
+/// [Update], in [AppState::InGame], after [build].
+pub(super) fn update_stockpile(
+    local: LocalEmpire,
+    difficulty: Res<Difficulty>,
+    q_empire: Query<Ref<Resources>>,
+    q_system: Query<&Owner, With<PlanetarySystem>>,
+    q_changed: Query<(), (With<PlanetarySystem>, Changed<Owner>)>,
+    mut q_energy: Query<(&mut Text, Ref<EnergyText>)>,
+    mut q_income: Query<&mut Text, (With<IncomeText>, Without<EnergyText>)>,
+) {
+    let Some(resources) = local.get().and_then(|empire| q_empire.get(empire).ok()) else {
+        return;
+    };
+    let Ok((mut energy, marker)) = q_energy.get_single_mut() else {
+        return;
+    };
+    if !marker.is_added() && !resources.is_changed() && q_changed.is_empty() {
+        return;
+    }
+
+    let systems = count_systems(&q_system);
+    let count = local
+        .get()
+        .and_then(|empire| systems.get(&empire).copied())
+        .unwrap_or(0);
+    let income = Income::new(count, true, *difficulty);
+    set_text(
+        &mut energy,
+        format!("Energy {:.1} ({:+.2})", resources.energy, income.total()),
+    );
+
+    let mut lines = vec!["Income per tick".to_string()];
+    lines.extend(
+        income
+            .sources()
+            .iter()
+            .filter(|(_, energy)| *energy != 0.0)
+            .map(|(source, energy)| format!("{}: {:+.2}", source, energy)),
+    );
+    for mut text in q_income.iter_mut() {
+        set_text(&mut text, lines.join("\n"));
+    }
+}
+
+/// Show the in-game date, when it changes.
+/// # Schedule
+/// [Update], in [AppState::InGame], after [build].
+pub(super) fn update_date(tick: Res<GameTick>, mut q_date: Query<(&mut Text, Ref<DateText>)>) {
+    for (mut text, marker) in q_date.iter_mut() {
+        if marker.is_added() || tick.is_changed() {
+            set_text(&mut text, GameDate::from(*tick).to_string());
+        }
+    }
+}
+
+/// Highlight the button of the current [GameSpeed].
+/// # Schedule
+/// [Update], in [AppState::InGame], after [build].
+pub(super) fn highlight_speed(
+    speed: Res<GameSpeed>,
+    ui_config: Res<UiConfigs>,
+    mut q_button: Query<(&mut BackgroundColor, Ref<SpeedButton>)>,
+) {
+    for (mut background, button) in q_button.iter_mut() {
+        if !button.is_added() && !speed.is_changed() {
+            continue;
+        }
+        let color = if button.0 == *speed {
+            ui_config.menu.color_focus
+        } else {
+            ui_config.menu.color_none
+        };
+        background.set_if_neq(BackgroundColor(color));
+    }
+}
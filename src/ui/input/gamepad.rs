@@ -0,0 +1,131 @@
+//! Drive the virtual pointer with gamepads.
+//!
+//! - the left stick moves the pointer.
+//! - the triggers zoom like the mouse wheel.
+//! - `South`, `East` and `West` act as the left, right and middle mouse
+//!     buttons.
+use super::parsed::{MouseMotion as MouseMotion2, MouseWheel as MouseWheel2};
+use crate::ui::settings::InputSettings;
+use bevy::{
+    input::{mouse::MouseButtonInput, ButtonState},
+    prelude::*,
+    render::camera::RenderTarget,
+    window::{PrimaryWindow, WindowRef},
+};
+use bevy_mod_picking::{
+    pointer::{InputMove, Location},
+    prelude::PointerId,
+};
+
+/// The gamepad buttons acting as mouse buttons.
+const BUTTONS: [(GamepadButtonType, MouseButton); 3] = [
+    (GamepadButtonType::South, MouseButton::Left),
+    (GamepadButtonType::East, MouseButton::Right),
+    (GamepadButtonType::West, MouseButton::Middle),
+];
+
+/// The wheel scroll of a fully pressed trigger, per second.
+const TRIGGER_ZOOM_SPEED: f32 = 400.0;
+
+/// Move the pointer with the left stick.
+/// # Schedule
+/// [First], after mouse motion.
+pub(super) fn motion(
+    q_window: Query<Entity, With<PrimaryWindow>>,
+    mut ew_motion: EventWriter<InputMove>,
+    mut motion: ResMut<MouseMotion2>,
+    gamepads: Res<Gamepads>,
+    axes: Res<Axis<GamepadAxis>>,
+    time: Res<Time<Real>>,
+    settings: Res<InputSettings>,
+) {
+    let mut stick = Vec2::ZERO;
+    for gamepad in gamepads.iter() {
+        let axis = |axis_type| {
+            axes.get(GamepadAxis::new(gamepad, axis_type))
+                .unwrap_or_default()
+        };
+        // the stick points up, the screen goes down.
+        stick += Vec2::new(
+            axis(GamepadAxisType::LeftStickX),
+            -axis(GamepadAxisType::LeftStickY),
+        );
+    }
+    if stick == Vec2::ZERO {
+        return;
+    }
+    let Ok(window) = q_window.get_single() else {
+        return;
+    };
+
+    let delta = motion.add_delta(
+        stick.clamp_length_max(1.0) * settings.gamepad_cursor_speed * time.delta_seconds(),
+    );
+    ew_motion.send(InputMove::new(
+        PointerId::Mouse,
+        Location {
+            target: RenderTarget::Window(WindowRef::Primary)
+                .normalize(Some(window))
+                .unwrap(),
+            position: motion.position,
+        },
+        delta,
+    ));
+    motion.post_update();
+}
+
+/// Zoom with the triggers, the right one zooms in.
+/// # Schedule
+/// [First], after mouse wheel.
+pub(super) fn wheel(
+    mut wheel: ResMut<MouseWheel2>,
+    gamepads: Res<Gamepads>,
+    triggers: Res<Axis<GamepadButton>>,
+    time: Res<Time<Real>>,
+) {
+    for gamepad in gamepads.iter() {
+        let trigger = |button_type| {
+            triggers
+                .get(GamepadButton::new(gamepad, button_type))
+                .unwrap_or_default()
+        };
+        let zoom =
+            trigger(GamepadButtonType::RightTrigger2) - trigger(GamepadButtonType::LeftTrigger2);
+        if zoom != 0.0 {
+            wheel.add_delta(Vec2::Y * zoom * TRIGGER_ZOOM_SPEED * time.delta_seconds());
+        }
+    }
+}
+
+/// Turn gamepad buttons into mouse button events, so that they go through
+/// the same path as the mouse.
+/// # Schedule
+/// [First], before mouse button.
+pub(super) fn button(
+    q_window: Query<Entity, With<PrimaryWindow>>,
+    mut ew_button: EventWriter<MouseButtonInput>,
+    gamepads: Res<Gamepads>,
+    buttons: Res<ButtonInput<GamepadButton>>,
+) {
+    let Ok(window) = q_window.get_single() else {
+        return;
+    };
+
+    for gamepad in gamepads.iter() {
+        for (button_type, button) in BUTTONS {
+            let gamepad_button = GamepadButton::new(gamepad, button_type);
+            let state = if buttons.just_pressed(gamepad_button) {
+                ButtonState::Pressed
+            } else if buttons.just_released(gamepad_button) {
+                ButtonState::Released
+            } else {
+                continue;
+            };
+            ew_button.send(MouseButtonInput {
+                button,
+                state,
+                window,
+            });
+        }
+    }
+}
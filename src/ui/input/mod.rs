@@ -1,10 +1,15 @@
 //! Handle mouse and keyboard input
+mod gamepad;
 mod mouse;
 mod parsed;
 
 use bevy::prelude::*;
 use bevy_mod_picking::picking_core::PickSet;
-pub use parsed::{MouseMotion, MouseWheel};
+pub use parsed::{Button, MouseButtons, MouseMotion, MouseWheel};
+
+/// Move the virtual pointer to a position of the window, in pixels.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct WarpPointer(pub Vec2);
 
 pub struct InputPlugin;
 
@@ -12,6 +17,8 @@ impl Plugin for InputPlugin {
     fn build(&self, app: &mut App) {
         app.insert_resource(parsed::MouseMotion::default())
             .insert_resource(parsed::MouseWheel::default())
+            .insert_resource(parsed::MouseButtons::default())
+            .add_event::<WarpPointer>()
             .add_systems(Startup, mouse::setup)
             .add_systems(
                 First,
@@ -19,7 +26,18 @@ impl Plugin for InputPlugin {
             )
             .add_systems(
                 First,
-                (mouse::button, mouse::motion, mouse::wheel).in_set(PickSet::Input),
+                (
+                    (
+                        mouse::motion,
+                        gamepad::motion,
+                        mouse::warp,
+                        gamepad::button,
+                        mouse::button,
+                    )
+                        .chain(),
+                    (mouse::wheel, gamepad::wheel).chain(),
+                )
+                    .in_set(PickSet::Input),
             )
             .add_systems(Update, mouse::sync);
     }
@@ -1,4 +1,6 @@
-use super::parsed::{MouseMotion as MouseMotion2, MouseWheel as MouseWheel2};
+use super::parsed::{MouseButtons, MouseMotion as MouseMotion2, MouseWheel as MouseWheel2};
+use super::WarpPointer;
+use crate::ui::settings::InputSettings;
 use crate::ui::PrimaryCamera;
 use bevy::{
     input::{
@@ -93,12 +95,14 @@ pub(super) fn motion(
     mut er_motion: EventReader<MouseMotion>,
     mut ew_motion: EventWriter<InputMove>,
     mut motion: ResMut<MouseMotion2>,
+    settings: Res<InputSettings>,
 ) {
     let window = q_window
         .get_single()
         .expect("Fail to find primary window in Bevy");
     let viewport = Vec2::new(window.1.width(), window.1.height()) - 1.0;
     motion.refresh(viewport);
+    motion.border_width = settings.edge_scroll.border_width;
     for event in er_motion.read() {
         let delta = motion.add_delta(event.delta);
         ew_motion.send(InputMove::new(
@@ -126,14 +130,58 @@ pub(super) fn wheel(mut er_wheel: EventReader<MouseWheel>, mut wheel: ResMut<Mou
     }
 }
 
-/// Convert mouse button events.
+/// Move the pointer to the requested positions.
 /// # Schedule
-/// [First]
+/// [First], after mouse and gamepad motion.
+pub(super) fn warp(
+    q_window: Query<Entity, With<PrimaryWindow>>,
+    mut er_warp: EventReader<WarpPointer>,
+    mut ew_motion: EventWriter<InputMove>,
+    mut motion: ResMut<MouseMotion2>,
+) {
+    let Ok(window) = q_window.get_single() else {
+        return;
+    };
+    for event in er_warp.read() {
+        // not a motion, so that a held button does not start dragging.
+        let delta = motion.warp(event.0);
+        ew_motion.send(InputMove::new(
+            PointerId::Mouse,
+            Location {
+                target: RenderTarget::Window(WindowRef::Primary)
+                    .normalize(Some(window))
+                    .unwrap(),
+                position: motion.position,
+            },
+            delta,
+        ));
+        motion.post_update();
+    }
+}
+
+/// Convert mouse button events, and track clicks and drags.
+/// # Schedule
+/// [First], after [motion].
 pub(super) fn button(
     mut er_button: EventReader<MouseButtonInput>,
     mut ew_button: EventWriter<InputPress>,
+    mut buttons: ResMut<MouseButtons>,
+    motion: Res<MouseMotion2>,
+    time: Res<Time<Real>>,
+    settings: Res<InputSettings>,
 ) {
+    buttons.refresh(motion.motion, settings.drag_threshold);
     for event in er_button.read() {
+        if let Some(tracked) = buttons.get_mut(event.button) {
+            match event.state {
+                ButtonState::Pressed => tracked.press(),
+                ButtonState::Released => tracked.release(
+                    time.elapsed_seconds_f64(),
+                    settings.double_click_time as f64,
+                ),
+            }
+        }
+
         let button = match event.button {
             MouseButton::Left => PointerButton::Primary,
             MouseButton::Right => PointerButton::Secondary,
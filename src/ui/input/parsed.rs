@@ -1,6 +1,6 @@
 //! For parsed inputs
 
-use bevy::{math::Vec2, prelude::Resource, reflect::Reflect};
+use bevy::{input::mouse::MouseButton, math::Vec2, prelude::Resource, reflect::Reflect};
 
 /// A resource to store input of the mouse.
 #[derive(Debug, Default, Clone, Copy, Resource, Reflect)]
@@ -16,8 +16,15 @@ pub struct MouseMotion {
     /// Indicate if the mouse pointer is on the viewport border. `x` indicate the
     /// horizontal axis, -1 is on the left and 1 is on the right; `y` indicate
     /// the vertical axis, -1 is the top and 1 is the bottom. 0 always indicate
-    /// not on the border for the respected axis.
+    /// not on the border for the respected axis. Within the border, the
+    /// magnitude grows from 0 to 1 toward the edge.
     pub on_border: Vec2,
+    /// The width of the viewport border, in pixels.
+    pub border_width: f32,
+    /// Indicate if the mouse pointer is locked in place. The motion is still
+    /// recorded, but the position does not change and it is never on the
+    /// border.
+    pub locked: bool,
 }
 
 impl MouseMotion {
@@ -31,17 +38,33 @@ impl MouseMotion {
     /// delta after the constraint.
     pub(super) fn add_delta(&mut self, delta: Vec2) -> Vec2 {
         self.motion += delta;
+        if self.locked {
+            return Vec2::ZERO;
+        }
         let new_delta = delta.clamp(-self.position, self.viewport - self.position);
         self.position += new_delta;
         new_delta
     }
+    /// Move the mouse pointer to a position, without any motion. Return the
+    /// actual delta after the constraint.
+    pub(super) fn warp(&mut self, position: Vec2) -> Vec2 {
+        if self.locked {
+            return Vec2::ZERO;
+        }
+        let old = self.position;
+        self.position = position.clamp(Vec2::ZERO, self.viewport);
+        self.position - old
+    }
     /// Compute additional fields for motion. Must be called at the end of input update
     pub(super) fn post_update(&mut self) {
-        let epsilon = f32::EPSILON * self.viewport;
-        self.on_border.x = f32::from(self.position.x >= self.viewport.x - epsilon.x)
-            - f32::from(self.position.x <= 0.0 + epsilon.x);
-        self.on_border.y = f32::from(self.position.y >= self.viewport.y - epsilon.y)
-            - f32::from(self.position.y <= 0.0 + epsilon.y);
+        if self.locked {
+            self.on_border = Vec2::ZERO;
+            return;
+        }
+        // at least one pixel, so that the edge itself is always on it.
+        let width = self.border_width.max(1.0);
+        let depth = |position: Vec2| ((Vec2::splat(width) - position) / width).max(Vec2::ZERO);
+        self.on_border = depth(self.viewport - self.position) - depth(self.position);
     }
 }
 
@@ -64,23 +87,246 @@ impl MouseWheel {
     }
 }
 
-pub struct Button {}
+/// The state of a mouse button, tracked over frames. Presses and releases
+/// are turned into clicks, multiple clicks and drags.
+#[derive(Debug, Default, Clone, Copy, Reflect)]
+pub struct Button {
+    pressed: bool,
+    /// The pointer motion since the button was pressed.
+    travel: Vec2,
+    dragging: bool,
+    /// The time of the last click, in seconds.
+    last_click: f64,
+    click_count: u32,
+
+    just_pressed: bool,
+    just_released: bool,
+    clicked: bool,
+    drag_started: bool,
+    drag_ended: bool,
+}
 
 impl Button {
-    pub(super) fn press(&mut self) {}
-    pub(super) fn release(&mut self) {}
+    /// Prepare for the new frame data, with the pointer motion of the frame.
+    pub(super) fn refresh(&mut self, motion: Vec2, drag_threshold: f32) {
+        self.just_pressed = false;
+        self.just_released = false;
+        self.clicked = false;
+        self.drag_started = false;
+        self.drag_ended = false;
+
+        if self.pressed {
+            self.travel += motion;
+            if !self.dragging && self.travel.length() > drag_threshold {
+                self.dragging = true;
+                self.drag_started = true;
+            }
+        }
+    }
+
+    pub(super) fn press(&mut self) {
+        self.pressed = true;
+        self.just_pressed = true;
+        self.travel = Vec2::ZERO;
+    }
+
+    /// Release the button at `time`, in seconds. It is a click if it was
+    /// not dragged, and counts with the previous clicks if they were less
+    /// than `double_click_time` apart.
+    pub(super) fn release(&mut self, time: f64, double_click_time: f64) {
+        if !self.pressed {
+            return;
+        }
+        self.pressed = false;
+        self.just_released = true;
+
+        if self.dragging {
+            self.dragging = false;
+            self.drag_ended = true;
+            self.click_count = 0;
+            return;
+        }
+
+        self.clicked = true;
+        if self.click_count > 0 && time - self.last_click <= double_click_time {
+            self.click_count += 1;
+        } else {
+            self.click_count = 1;
+        }
+        self.last_click = time;
+    }
 
     pub fn pressed(&self) -> bool {
-        todo!()
+        self.pressed
+    }
+    pub fn just_pressed(&self) -> bool {
+        self.just_pressed
+    }
+    pub fn just_released(&self) -> bool {
+        self.just_released
     }
+    /// Released this frame, without being dragged.
     pub fn clicked(&self) -> bool {
-        todo!()
+        self.clicked
     }
+    /// Clicked this frame, as the second click in a row.
     pub fn double_clicked(&self) -> bool {
-        todo!()
+        self.clicked && self.click_count == 2
+    }
+    /// The number of clicks in a row, including the click of this frame.
+    pub fn click_count(&self) -> u32 {
+        self.click_count
+    }
+    /// Pressed and moved further than the drag threshold.
+    pub fn dragging(&self) -> bool {
+        self.dragging
+    }
+    pub fn drag_started(&self) -> bool {
+        self.drag_started
+    }
+    pub fn drag_ended(&self) -> bool {
+        self.drag_ended
+    }
+    /// The pointer motion since the button was pressed.
+    pub fn travel(&self) -> Vec2 {
+        self.travel
+    }
+}
+
+/// A resource to store the state of mouse buttons.
+#[derive(Debug, Default, Clone, Copy, Resource, Reflect)]
+pub struct MouseButtons {
+    pub left: Button,
+    pub right: Button,
+    pub middle: Button,
+}
+
+impl MouseButtons {
+    pub fn get(&self, button: MouseButton) -> Option<&Button> {
+        match button {
+            MouseButton::Left => Some(&self.left),
+            MouseButton::Right => Some(&self.right),
+            MouseButton::Middle => Some(&self.middle),
+            _ => None,
+        }
+    }
+
+    pub(super) fn get_mut(&mut self, button: MouseButton) -> Option<&mut Button> {
+        match button {
+            MouseButton::Left => Some(&mut self.left),
+            MouseButton::Right => Some(&mut self.right),
+            MouseButton::Middle => Some(&mut self.middle),
+            _ => None,
+        }
+    }
+
+    /// Prepare for the new frame data.
+    pub(super) fn refresh(&mut self, motion: Vec2, drag_threshold: f32) {
+        self.left.refresh(motion, drag_threshold);
+        self.right.refresh(motion, drag_threshold);
+        self.middle.refresh(motion, drag_threshold);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const THRESHOLD: f32 = 4.0;
+    const DOUBLE_CLICK: f64 = 0.3;
+
+    /// A press and a release on two frames, without motion.
+    fn click(button: &mut Button, time: f64) {
+        button.refresh(Vec2::ZERO, THRESHOLD);
+        button.press();
+        button.refresh(Vec2::ZERO, THRESHOLD);
+        button.release(time, DOUBLE_CLICK);
+    }
+
+    #[test]
+    fn click_without_motion() {
+        let mut button = Button::default();
+        button.refresh(Vec2::ZERO, THRESHOLD);
+        button.press();
+        assert!(button.pressed() && button.just_pressed());
+        assert!(!button.clicked());
+
+        button.refresh(Vec2::new(1.0, 1.0), THRESHOLD);
+        assert!(button.pressed() && !button.just_pressed());
+        button.release(1.0, DOUBLE_CLICK);
+        assert!(!button.pressed() && button.just_released());
+        assert!(button.clicked() && !button.double_clicked());
+        assert_eq!(button.click_count(), 1);
+        assert!(!button.dragging() && !button.drag_ended());
+
+        // the events of a frame are gone in the next one.
+        button.refresh(Vec2::ZERO, THRESHOLD);
+        assert!(!button.just_released() && !button.clicked());
+        assert_eq!(button.click_count(), 1);
+    }
+
+    #[test]
+    fn count_clicks_in_a_row() {
+        let mut button = Button::default();
+        click(&mut button, 1.0);
+        click(&mut button, 1.2);
+        assert!(button.double_clicked());
+        assert_eq!(button.click_count(), 2);
+
+        click(&mut button, 1.4);
+        assert!(button.clicked() && !button.double_clicked());
+        assert_eq!(button.click_count(), 3);
+
+        // too late to count with the previous ones.
+        click(&mut button, 2.0);
+        assert!(!button.double_clicked());
+        assert_eq!(button.click_count(), 1);
+    }
+
+    #[test]
+    fn drag_past_threshold() {
+        let mut button = Button::default();
+        click(&mut button, 1.0);
+
+        button.refresh(Vec2::ZERO, THRESHOLD);
+        button.press();
+        button.refresh(Vec2::new(3.0, 0.0), THRESHOLD);
+        assert!(!button.dragging() && !button.drag_started());
+        button.refresh(Vec2::new(0.0, 3.0), THRESHOLD);
+        assert!(button.dragging() && button.drag_started());
+        assert_eq!(button.travel(), Vec2::new(3.0, 3.0));
+        button.refresh(Vec2::ZERO, THRESHOLD);
+        assert!(button.dragging() && !button.drag_started());
+
+        button.release(1.1, DOUBLE_CLICK);
+        assert!(button.just_released() && button.drag_ended());
+        assert!(!button.dragging() && !button.clicked());
+
+        // a drag breaks a row of clicks.
+        click(&mut button, 1.2);
+        assert!(!button.double_clicked());
+        assert_eq!(button.click_count(), 1);
+    }
+
+    #[test]
+    fn travel_only_while_pressed() {
+        let mut button = Button::default();
+        button.refresh(Vec2::new(100.0, 0.0), THRESHOLD);
+        assert_eq!(button.travel(), Vec2::ZERO);
+
+        button.press();
+        assert_eq!(button.travel(), Vec2::ZERO);
+        button.refresh(Vec2::new(1.0, 0.0), THRESHOLD);
+        assert_eq!(button.travel(), Vec2::new(1.0, 0.0));
+        assert!(!button.dragging());
     }
 
-    pub fn click_count(&self) -> bool {
-        todo!()
+    #[test]
+    fn ignore_release_without_press() {
+        let mut button = Button::default();
+        button.refresh(Vec2::ZERO, THRESHOLD);
+        button.release(1.0, DOUBLE_CLICK);
+        assert!(!button.just_released() && !button.clicked());
+        assert_eq!(button.click_count(), 0);
     }
 }
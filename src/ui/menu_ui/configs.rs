@@ -1,8 +1,12 @@
 //! the configuration of ui
+use super::MenuState;
+use crate::ui::settings::VideoSettings;
 use bevy::{
     asset::Handle,
     color::{palettes::tailwind, Alpha, Color},
-    prelude::Resource,
+    ecs::world::World,
+    hierarchy::DespawnRecursiveExt,
+    prelude::{Entity, OnEnter, Res, ResMut, Resource, State, StateScoped},
     reflect::Reflect,
     text::Font,
 };
@@ -53,3 +57,37 @@ impl Default for MenuConfigs {
         }
     }
 }
+
+/// Follow the UI scale of the [VideoSettings].
+/// # Schedule
+/// [bevy::prelude::PreUpdate], when [VideoSettings] changes.
+pub fn sync_scale(settings: Res<VideoSettings>, mut configs: ResMut<Configs>) {
+    if configs.scale != settings.ui_scale {
+        configs.scale = settings.ui_scale;
+    }
+}
+
+/// Rebuild the open menu page so that it follows the new [Configs].
+///
+/// Entering the same [MenuState] again does not run its `OnEnter` systems,
+/// so the page is despawned and its setup is run by hand.
+/// # Schedule
+/// [bevy::prelude::Update], in [MenuState], when [Configs] changes.
+pub fn rebuild_page(world: &mut World) {
+    let Some(page) = world
+        .get_resource::<State<MenuState>>()
+        .map(|state| *state.get())
+    else {
+        return;
+    };
+    let scoped: Vec<Entity> = world
+        .query::<(Entity, &StateScoped<MenuState>)>()
+        .iter(world)
+        .filter(|(_, scope)| scope.0 == page)
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in scoped {
+        world.entity_mut(entity).despawn_recursive();
+    }
+    world.run_schedule(OnEnter(page));
+}
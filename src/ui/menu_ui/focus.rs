@@ -0,0 +1,128 @@
+//! Navigate between menu buttons with the keyboard or a gamepad.
+//!
+//! The virtual pointer jumps onto the focused button, so that hovering and
+//! clicking go through the usual picking events.
+//! - arrows and the D-pad move to the nearest button in their direction.
+//! - `Tab` moves to the next button in reading order, `Shift + Tab` to the
+//!     previous one.
+//! - `Enter` clicks on the focused button, gamepads click with `South`.
+
+use super::menu_pages::text_input::TextInputFocus;
+use super::menu_pages::Rebinding;
+use crate::ui::input::{MouseMotion, WarpPointer};
+use bevy::input::{mouse::MouseButtonInput, ButtonState};
+use bevy::math::FloatOrd;
+use bevy::prelude::*;
+use bevy::window::PrimaryWindow;
+
+/// The keys and gamepad buttons moving toward a direction of the screen.
+const DIRECTIONS: [(KeyCode, GamepadButtonType, Vec2); 4] = [
+    (KeyCode::ArrowUp, GamepadButtonType::DPadUp, Vec2::NEG_Y),
+    (KeyCode::ArrowDown, GamepadButtonType::DPadDown, Vec2::Y),
+    (KeyCode::ArrowLeft, GamepadButtonType::DPadLeft, Vec2::NEG_X),
+    (KeyCode::ArrowRight, GamepadButtonType::DPadRight, Vec2::X),
+];
+
+/// The area of a visible button, in pixels.
+fn button_rects(
+    q_button: &Query<(&Node, &GlobalTransform, &ViewVisibility), With<Button>>,
+) -> Vec<Rect> {
+    q_button
+        .iter()
+        .filter(|(.., visibility)| visibility.get())
+        .map(|(node, transform, _)| {
+            Rect::from_center_size(transform.translation().xy(), node.size())
+        })
+        .collect()
+}
+
+/// Move the pointer to the next button.
+/// # Schedule
+/// [Update], in [crate::AppState::InMenu].
+#[allow(clippy::too_many_arguments)]
+pub fn navigate(
+    mut ew_warp: EventWriter<WarpPointer>,
+    keys: Res<ButtonInput<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+    text_focus: Res<TextInputFocus>,
+    rebinding: Res<Rebinding>,
+    motion: Res<MouseMotion>,
+    q_button: Query<(&Node, &GlobalTransform, &ViewVisibility), With<Button>>,
+) {
+    // the keys are taken by a text input or a key binding.
+    if text_focus.0.is_some() || rebinding.0.is_some() {
+        return;
+    }
+    let pressed = |key: KeyCode, button_type: GamepadButtonType| {
+        keys.just_pressed(key)
+            || gamepads.iter().any(|gamepad| {
+                gamepad_buttons.just_pressed(GamepadButton::new(gamepad, button_type))
+            })
+    };
+    let from = motion.position;
+
+    let target = if keys.just_pressed(KeyCode::Tab) {
+        // reading order, top to bottom then left to right.
+        let mut rects = button_rects(&q_button);
+        rects.sort_by_key(|rect| (FloatOrd(rect.center().y), FloatOrd(rect.center().x)));
+        if keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]) {
+            rects.reverse();
+        }
+        let current = rects.iter().position(|rect| rect.contains(from));
+        let next = current.map_or(0, |i| i + 1);
+        rects.get(next).or(rects.first()).copied()
+    } else if let Some((.., direction)) = DIRECTIONS
+        .into_iter()
+        .find(|(key, button_type, _)| pressed(*key, *button_type))
+    {
+        // prefer buttons straight in the direction over closer ones aside.
+        button_rects(&q_button)
+            .into_iter()
+            .filter(|rect| !rect.contains(from))
+            .filter_map(|rect| {
+                let offset = rect.center() - from;
+                let along = offset.dot(direction);
+                let aside = offset.perp_dot(direction).abs();
+                (along > 0.0).then_some((rect, along + 2.0 * aside))
+            })
+            .min_by_key(|(_, score)| FloatOrd(*score))
+            .map(|(rect, _)| rect)
+    } else {
+        None
+    };
+
+    if let Some(rect) = target {
+        ew_warp.send(WarpPointer(rect.center()));
+    }
+}
+
+/// Click on the focused button with `Enter`. Gamepads already click with
+/// their buttons.
+/// # Schedule
+/// [Update], in [crate::AppState::InMenu].
+pub fn activate(
+    q_window: Query<Entity, With<PrimaryWindow>>,
+    mut ew_button: EventWriter<MouseButtonInput>,
+    keys: Res<ButtonInput<KeyCode>>,
+    text_focus: Res<TextInputFocus>,
+    rebinding: Res<Rebinding>,
+) {
+    if text_focus.0.is_some()
+        || rebinding.0.is_some()
+        || !keys.any_just_pressed([KeyCode::Enter, KeyCode::NumpadEnter])
+    {
+        return;
+    }
+    let Ok(window) = q_window.get_single() else {
+        return;
+    };
+
+    for state in [ButtonState::Pressed, ButtonState::Released] {
+        ew_button.send(MouseButtonInput {
+            button: MouseButton::Left,
+            state,
+            window,
+        });
+    }
+}
@@ -0,0 +1,164 @@
+//! The loading screen, over the wallpaper during [AppStateLoading].
+//!
+//! It shows the current stage, a bar of the whole [LoadingProgress]
+//! and a tip that changes every few seconds. A local game can be cancelled
+//! back to the page it was started from.
+
+use super::menu_pages::UiMenuPageExt;
+use super::{MenuState, UiCamera, UiConfigs};
+use crate::game_map::gen::{CancelLoading, LoadingProgress};
+use crate::states::{AppState, AppStateLoading, LoadSource};
+use bevy::prelude::*;
+use bevy_mod_picking::picking_core::Pickable;
+use sickle_ui::prelude::*;
+
+/// The tips shown while loading, in turn.
+const TIPS: [&str; 6] = [
+    "Drag the left mouse button to select several fleets at once.",
+    "Right click a planetary system to send the selected fleets there.",
+    "Every owned planetary system yields energy on each tick.",
+    "The top-down view shows the whole galaxy at once.",
+    "Quick saves are listed in Load Game, with a picture of the game.",
+    "The same seed always yields the same galaxy.",
+];
+
+/// The filled part of the progress bar.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub(super) struct ProgressFill;
+
+/// The text of the current stage.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub(super) struct StageText;
+
+/// The text of the current tip, and its index in [TIPS].
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub(super) struct TipText(usize);
+
+/// Spawn the loading screen.
+/// # Schedule
+/// Enter [AppStateLoading].
+pub(super) fn setup(
+    mut commands: Commands,
+    ui_config: Res<UiConfigs>,
+    progress: Res<LoadingProgress>,
+    app_state: Res<State<AppState>>,
+    q_camera: Query<Entity, With<UiCamera>>,
+) {
+    let camera = q_camera.single();
+    let text_style = TextStyle {
+        font: ui_config.text.font.clone(),
+        font_size: 16.0 * ui_config.scale * 1.5,
+        color: ui_config.text.color,
+    };
+
+    let screen = commands
+        .spawn(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(0.0),
+                width: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(12.0 * ui_config.scale),
+                padding: UiRect::all(Val::Px(48.0 * ui_config.scale)),
+                ..default()
+            },
+            background_color: ui_config.menu.color_none.into(),
+            ..default()
+        })
+        .insert(Name::new("Loading Screen"))
+        .insert(TargetCamera(camera))
+        .insert(StateScoped(AppStateLoading))
+        .insert(Pickable::IGNORE)
+        .with_children(|parent| {
+            parent
+                .spawn(TextBundle::from_section(TIPS[0], text_style.clone()))
+                .insert(TipText(0))
+                .insert(Pickable::IGNORE);
+            parent
+                .spawn(TextBundle::from_section(
+                    progress.stage.name(),
+                    text_style.clone(),
+                ))
+                .insert(StageText)
+                .insert(Pickable::IGNORE);
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(60.0),
+                        height: Val::Px(12.0 * ui_config.scale),
+                        border: UiRect::all(Val::Px(1.0)),
+                        ..default()
+                    },
+                    border_color: ui_config.text.color.into(),
+                    ..default()
+                })
+                .insert(Pickable::IGNORE)
+                .with_children(|bar| {
+                    bar.spawn(NodeBundle {
+                        style: Style {
+                            width: Val::Percent(progress.total() * 100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        background_color: ui_config.text.color.into(),
+                        ..default()
+                    })
+                    .insert(ProgressFill)
+                    .insert(Pickable::IGNORE);
+                });
+        })
+        .id();
+
+    // the peers of an online game go on without us.
+    if *app_state.get() != AppState::Loading(LoadSource::FromOnline) {
+        commands
+            .ui_builder(screen)
+            ._button(&ui_config, ("Cancel", button_cancel_action));
+    }
+}
+
+fn button_cancel_action(
+    app_state: Res<State<AppState>>,
+    mut next_app_state: ResMut<NextState<AppState>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut ew_cancel: EventWriter<CancelLoading>,
+) {
+    // both states change at once, the page would be lost otherwise.
+    next_app_state.set(AppState::InMenu);
+    menu_state.set(match app_state.get() {
+        AppState::Loading(LoadSource::FromLocal) => MenuState::LoadGamePage,
+        _ => MenuState::NewGamePage,
+    });
+    ew_cancel.send(CancelLoading);
+}
+
+/// Show the [LoadingProgress].
+/// # Schedule
+/// [Update], in [AppStateLoading], when [LoadingProgress] changes.
+pub(super) fn update(
+    progress: Res<LoadingProgress>,
+    mut q_fill: Query<&mut Style, With<ProgressFill>>,
+    mut q_stage: Query<&mut Text, With<StageText>>,
+) {
+    for mut style in q_fill.iter_mut() {
+        style.width = Val::Percent(progress.total() * 100.0);
+    }
+    for mut text in q_stage.iter_mut() {
+        text.sections[0].value = format!(
+            "{}... {:.0}%",
+            progress.stage.name(),
+            progress.progress * 100.0
+        );
+    }
+}
+
+/// Show the next tip.
+/// # Schedule
+/// [Update], in [AppStateLoading], on a timer.
+pub(super) fn next_tip(mut q_tip: Query<(&mut Text, &mut TipText)>) {
+    for (mut text, mut tip) in q_tip.iter_mut() {
+        tip.0 = (tip.0 + 1) % TIPS.len();
+        text.sections[0].value = TIPS[tip.0].to_string();
+    }
+}
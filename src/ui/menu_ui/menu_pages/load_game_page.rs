@@ -1,38 +1,393 @@
-use super::{menu_page::MenuUiBuilderExt0, MenuState, UiCamera, UiConfigs};
+//! The load game page, a browser of the saves on the disk.
+//!
+//! Each save shows its thumbnail, the empire of the player, the in-game
+//! date, the play time and the version of the game that wrote it. Saves
+//! of another format are listed with a warning and cannot be loaded.
+
+use super::{default_button_back_action, MenuState, UiCamera, UiConfigs, UiMenuPageExt};
+use crate::{
+    game_map::galaxy::{Galaxy, PrimaryGalaxy},
+    save::{delete_save, list_saves, saves_dir, SaveEntry, SaveFile},
+    states::LoadSource,
+    tick::GameDate,
+    ui::menu_ui::AppState,
+};
+use bevy::color::palettes::tailwind;
 use bevy::prelude::*;
+use bevy::render::render_asset::RenderAssetUsages;
+use bevy::render::texture::{CompressedImageFormats, ImageSampler, ImageType};
+use bevy::utils::HashMap;
+use bevy_mod_picking::prelude::Pickable;
 use sickle_ui::prelude::*;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The orders to list the saves in.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq)]
+pub enum SortOrder {
+    /// the last saved first.
+    #[default]
+    Newest,
+    Oldest,
+    /// the furthest in game first.
+    GameDate,
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 3] = [SortOrder::Newest, SortOrder::Oldest, SortOrder::GameDate];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SortOrder::Newest => "Newest",
+            SortOrder::Oldest => "Oldest",
+            SortOrder::GameDate => "In-Game Date",
+        }
+    }
+}
+
+/// The saves listed by the page.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct SaveBrowser {
+    saves: Vec<SaveEntry>,
+    order: SortOrder,
+    /// the save waiting for a second click to be deleted.
+    deleting: Option<PathBuf>,
+    /// the last failure to list, load or delete saves.
+    error: Option<String>,
+    /// the thumbnails of the saves, decoded once per scan.
+    thumbnails: HashMap<PathBuf, Handle<Image>>,
+}
+
+impl SaveBrowser {
+    /// List the saves of the save directory again, along with their
+    /// thumbnails.
+    fn scan(&mut self, images: &mut Assets<Image>) {
+        self.deleting = None;
+        self.error = None;
+        self.saves.clear();
+        self.thumbnails.clear();
+        let Some(dir) = saves_dir() else {
+            self.error = Some("There is no data directory to look for saves.".to_string());
+            return;
+        };
+        match list_saves(&dir) {
+            Ok(saves) => self.saves = saves,
+            Err(error) => self.error = Some(format!("Fail to list the saves: {}", error)),
+        }
+        for entry in self.saves.iter() {
+            if let Some(thumbnail) = load_thumbnail(&entry.thumbnail(), images) {
+                self.thumbnails.insert(entry.path.clone(), thumbnail);
+            }
+        }
+        self.sort();
+    }
+
+    /// Sort the saves in [SaveBrowser::order], the unreadable ones last.
+    fn sort(&mut self) {
+        let order = self.order;
+        self.saves.sort_by_key(|entry| match &entry.meta {
+            Ok(meta) => match order {
+                SortOrder::Newest => (0, u64::MAX - meta.saved_at),
+                SortOrder::Oldest => (0, meta.saved_at),
+                SortOrder::GameDate => (0, u64::MAX - meta.tick.0),
+            },
+            Err(_) => (1, 0),
+        });
+    }
+}
 
-pub fn spawn_load_game_menu(
+/// The container of the saves. Its content is rebuilt whenever the
+/// [SaveBrowser] changes.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct SaveList;
+
+/// The dropdown of the [SortOrder].
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct SortDropdown;
+
+pub fn setup(
     mut commands: Commands,
     q_camera: Query<Entity, With<UiCamera>>,
-    cfg: Res<UiConfigs>,
+    ui_config: Res<UiConfigs>,
+    mut browser: ResMut<SaveBrowser>,
+    mut images: ResMut<Assets<Image>>,
 ) {
     let camera = q_camera.single();
+    browser.scan(&mut images);
+    let order = SortOrder::ALL.iter().position(|o| *o == browser.order);
 
     commands
         .ui_builder(UiRoot)
-        .sub_menu_container(
-            &cfg,
-            "Start",
-            || {},
+        .menu_page(
+            &ui_config,
+            ("Return", default_button_back_action),
+            ("Refresh", button_refresh_action),
             |parent| {
                 parent
-                    .spawn(
-                        TextBundle::from_section(
-                            "Under Construction",
-                            TextStyle {
-                                font_size: 100.0,
-                                ..default()
-                            },
-                        )
-                        .with_text_justify(JustifyText::Center),
-                    )
+                    .column(|column| {
+                        column
+                            .row(|row| {
+                                row._label(&ui_config, "Sort By");
+                                row.column(|parent| {
+                                    parent
+                                        .dropdown(
+                                            SortOrder::ALL
+                                                .iter()
+                                                .map(|o| o.name().to_string())
+                                                .collect(),
+                                            order,
+                                        )
+                                        .insert(SortDropdown);
+                                })
+                                .style()
+                                .width(Val::Px(320.0 * ui_config.scale));
+                            })
+                            .style()
+                            .column_gap(Val::Px(16.0))
+                            .align_items(AlignItems::Center);
+
+                        column
+                            .column(|_| {})
+                            .insert(SaveList)
+                            .style()
+                            .row_gap(Val::Px(8.0))
+                            .width(Val::Percent(100.0));
+                    })
                     .style()
-                    .align_self(AlignSelf::Center)
-                    .justify_self(JustifySelf::Center);
+                    .row_gap(Val::Px(16.0))
+                    .width(Val::Percent(100.0))
+                    .padding(UiRect::all(Val::Px(16.0)));
             },
         )
         .insert(TargetCamera(camera))
         .insert(Name::new("Load Game Menu"))
         .insert(StateScoped(MenuState::LoadGamePage));
 }
+
+/// Sort the saves in the order picked by the player.
+/// # Schedule
+/// [Update], in [MenuState::LoadGamePage].
+pub fn sort(
+    mut browser: ResMut<SaveBrowser>,
+    q_dropdown: Query<&Dropdown, (With<SortDropdown>, Changed<Dropdown>)>,
+) {
+    let Some(order) = q_dropdown
+        .get_single()
+        .ok()
+        .and_then(Dropdown::value)
+        .map(|i| SortOrder::ALL[i])
+    else {
+        return;
+    };
+    if browser.order != order {
+        browser.order = order;
+        browser.sort();
+    }
+}
+
+/// The screenshot of a save, if there is one.
+fn load_thumbnail(path: &Path, images: &mut Assets<Image>) -> Option<Handle<Image>> {
+    let bytes = std::fs::read(path).ok()?;
+    let image = Image::from_buffer(
+        &bytes,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+        ImageSampler::Default,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    match image {
+        Ok(image) => Some(images.add(image)),
+        Err(error) => {
+            warn!("Invalid thumbnail {:?}: {}", path, error);
+            None
+        }
+    }
+}
+
+/// A duration in seconds, as hours and minutes.
+fn format_play_time(seconds: f64) -> String {
+    let minutes = (seconds / 60.0) as u64;
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+/// The time since `saved_at`, in seconds since the Unix epoch.
+fn format_age(saved_at: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    let age = now.saturating_sub(saved_at);
+    match age {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{} min ago", age / 60),
+        3600..=86399 => format!("{} h ago", age / 3600),
+        _ => format!("{} days ago", age / 86400),
+    }
+}
+
+/// Rebuild the list of saves when the [SaveBrowser] changes.
+/// # Schedule
+/// [Update], in [MenuState::LoadGamePage], after [sort].
+pub fn refresh(
+    mut commands: Commands,
+    ui_config: Res<UiConfigs>,
+    browser: Res<SaveBrowser>,
+    q_list: Query<(Entity, Ref<SaveList>)>,
+) {
+    let Ok((list, marker)) = q_list.get_single() else {
+        return;
+    };
+    if !marker.is_added() && !browser.is_changed() {
+        return;
+    }
+
+    commands.entity(list).despawn_descendants();
+    let mut builder = commands.ui_builder(list);
+
+    let text_style = TextStyle {
+        font: ui_config.text.font.clone(),
+        font_size: 16.0 * ui_config.scale * 1.5,
+        color: ui_config.text.color,
+    };
+    let warning_style = TextStyle {
+        color: tailwind::RED_500.into(),
+        ..text_style.clone()
+    };
+
+    if let Some(error) = &browser.error {
+        builder
+            .spawn(TextBundle::from_section(error, warning_style.clone()))
+            .insert(Pickable::IGNORE);
+    }
+    if browser.saves.is_empty() && browser.error.is_none() {
+        builder._label(&ui_config, "No saved game yet.");
+    }
+
+    for entry in browser.saves.iter() {
+        let thumbnail = browser.thumbnails.get(&entry.path).cloned();
+        let deleting = browser.deleting.as_ref() == Some(&entry.path);
+
+        builder
+            .row(|row| {
+                let mut image = match thumbnail {
+                    Some(thumbnail) => row.spawn(ImageBundle {
+                        image: UiImage::new(thumbnail),
+                        ..default()
+                    }),
+                    None => row.spawn(NodeBundle::default()),
+                };
+                image.insert(Pickable::IGNORE);
+                image
+                    .style()
+                    .width(Val::Px(160.0 * ui_config.scale))
+                    .height(Val::Px(90.0 * ui_config.scale))
+                    .background_color(ui_config.menu.color_none);
+
+                row.column(|column| match &entry.meta {
+                    Ok(meta) => {
+                        column._label(&ui_config, &meta.empire);
+                        column._label(
+                            &ui_config,
+                            &format!(
+                                "{}  -  played {}  -  saved {}  -  v{}",
+                                GameDate::from(meta.tick),
+                                format_play_time(meta.play_time),
+                                format_age(meta.saved_at),
+                                meta.game_version,
+                            ),
+                        );
+                        if !meta.is_compatible() {
+                            column
+                                .spawn(TextBundle::from_section(
+                                    "This save is from an incompatible version of the game.",
+                                    warning_style.clone(),
+                                ))
+                                .insert(Pickable::IGNORE);
+                        }
+                    }
+                    Err(error) => {
+                        column._label(&ui_config, &entry.path.display().to_string());
+                        column
+                            .spawn(TextBundle::from_section(
+                                format!("Unreadable save: {}", error),
+                                warning_style.clone(),
+                            ))
+                            .insert(Pickable::IGNORE);
+                    }
+                })
+                .style()
+                .row_gap(Val::Px(4.0))
+                .flex_grow(1.0);
+
+                if entry.meta.as_ref().is_ok_and(|meta| meta.is_compatible()) {
+                    let path = entry.path.clone();
+                    row._button(
+                        &ui_config,
+                        (
+                            "Load",
+                            move |commands: Commands,
+                                  browser: ResMut<SaveBrowser>,
+                                  app_state: ResMut<NextState<AppState>>| {
+                                load(&path, commands, browser, app_state)
+                            },
+                        ),
+                    );
+                }
+
+                let entry = entry.clone();
+                row._button(
+                    &ui_config,
+                    (
+                        if deleting { "Confirm" } else { "Delete" },
+                        move |mut browser: ResMut<SaveBrowser>,
+                              mut images: ResMut<Assets<Image>>| {
+                            // the first click asks for a second one.
+                            if browser.deleting.as_ref() != Some(&entry.path) {
+                                browser.deleting = Some(entry.path.clone());
+                                return;
+                            }
+                            match delete_save(&entry) {
+                                Ok(()) => {
+                                    info!("Deleted save {:?}.", entry.path);
+                                    browser.scan(&mut images);
+                                }
+                                Err(error) => {
+                                    browser.deleting = None;
+                                    browser.error =
+                                        Some(format!("Fail to delete the save: {}", error));
+                                }
+                            }
+                        },
+                    ),
+                );
+            })
+            .style()
+            .column_gap(Val::Px(16.0))
+            .align_items(AlignItems::Center);
+    }
+}
+
+/// Read the save at `path`, and load it.
+fn load(
+    path: &Path,
+    mut commands: Commands,
+    mut browser: ResMut<SaveBrowser>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    let file = match SaveFile::read(path) {
+        Ok(file) => file,
+        Err(error) => {
+            browser.error = Some(format!("Fail to load the save: {}", error));
+            return;
+        }
+    };
+    info!("Loading save {:?} at tick {}", path, file.game.tick.0);
+    // the map is generated again, then the save is restored into it.
+    commands.spawn((Galaxy, PrimaryGalaxy, file.game.params.clone()));
+    commands.insert_resource(file.game);
+    app_state.set(AppState::Loading(LoadSource::FromLocal));
+}
+
+fn button_refresh_action(mut browser: ResMut<SaveBrowser>, mut images: ResMut<Assets<Image>>) {
+    browser.scan(&mut images);
+}
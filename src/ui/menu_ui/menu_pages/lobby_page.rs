@@ -0,0 +1,263 @@
+use super::text_input::TextInput;
+use super::{MenuState, UiCamera, UiConfigs, UiMenuPageExt};
+use crate::empire::EMPIRE_COLORS;
+use crate::game_map::galaxy::gen::GalaxyGenParams;
+use crate::net::{self, lobby, Lobby, NetSession};
+use crate::AppState;
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::Pickable;
+use sickle_ui::prelude::*;
+
+/// The container of the player list and galaxy settings. Its content is
+/// rebuilt whenever the [Lobby] changes.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct LobbyPanel;
+
+/// The empire name of the local player.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct EmpireNameInput;
+
+pub fn setup(
+    mut commands: Commands,
+    q_camera: Query<Entity, With<UiCamera>>,
+    ui_config: Res<UiConfigs>,
+    session: Option<Res<NetSession>>,
+) {
+    let camera = q_camera.single();
+    let is_host = session.is_some_and(|session| session.is_host());
+
+    let content = |parent: &mut UiBuilder<Entity>| {
+        parent
+            .column(|column| {
+                column
+                    .column(|_| {})
+                    .insert(LobbyPanel)
+                    .style()
+                    .row_gap(Val::Px(8.0))
+                    .width(Val::Percent(100.0));
+
+                column
+                    .row(|row| {
+                        row._label(&ui_config, "Empire");
+                        row._text_input(&ui_config, "").insert(EmpireNameInput);
+                        row._button(&ui_config, ("Colour", button_color_action));
+                        row._button(&ui_config, ("Species", button_species_action));
+                    })
+                    .style()
+                    .column_gap(Val::Px(16.0))
+                    .align_items(AlignItems::Center);
+
+                if is_host {
+                    column
+                        .row(|row| {
+                            row._label(&ui_config, "Galaxy");
+                            row._button(&ui_config, ("Smaller", button_smaller_action));
+                            row._button(&ui_config, ("Larger", button_larger_action));
+                            row._button(&ui_config, ("Sparser", button_sparser_action));
+                            row._button(&ui_config, ("Denser", button_denser_action));
+                        })
+                        .style()
+                        .column_gap(Val::Px(16.0))
+                        .align_items(AlignItems::Center);
+                }
+            })
+            .style()
+            .row_gap(Val::Px(32.0))
+            .padding(UiRect::all(Val::Px(16.0)))
+            .width(Val::Percent(100.0));
+    };
+
+    let mut root = commands.ui_builder(UiRoot);
+    let mut builder = if is_host {
+        root.menu_page(
+            &ui_config,
+            ("Leave", button_leave_action),
+            ("Start", button_start_action),
+            content,
+        )
+    } else {
+        root.menu_page(
+            &ui_config,
+            ("Leave", button_leave_action),
+            ("Ready", button_ready_action),
+            content,
+        )
+    };
+
+    builder
+        .insert(TargetCamera(camera))
+        .insert(Name::new("Lobby Menu"))
+        .insert(StateScoped(MenuState::LobbyPage));
+}
+
+/// Rebuild the [LobbyPanel] after the [Lobby] changed.
+/// # Schedule
+/// [Update], in [MenuState::LobbyPage].
+pub fn refresh(
+    mut commands: Commands,
+    ui_config: Res<UiConfigs>,
+    lobby: Option<Res<Lobby>>,
+    session: Option<Res<NetSession>>,
+    q_panel: Query<(Entity, Ref<LobbyPanel>)>,
+    mut q_name: Query<&mut TextInput, With<EmpireNameInput>>,
+) {
+    let Ok((panel, marker)) = q_panel.get_single() else {
+        return;
+    };
+    if !marker.is_added() && !lobby.as_ref().is_some_and(|lobby| lobby.is_changed()) {
+        return;
+    }
+
+    commands.entity(panel).despawn_descendants();
+    let mut builder = commands.ui_builder(panel);
+
+    let (Some(lobby), Some(session)) = (lobby, session) else {
+        builder._label(&ui_config, "Connecting...");
+        return;
+    };
+
+    for slot in lobby.slots.iter() {
+        let player = slot.player;
+        let mut description = format!(
+            "{} - {} - {}",
+            slot.empire.name,
+            slot.empire.species.name(),
+            if slot.ready { "Ready" } else { "Not Ready" }
+        );
+        if player == session.local {
+            description += " (You)";
+        }
+
+        builder
+            .row(|row| {
+                row.spawn(NodeBundle::default())
+                    .insert(Pickable::IGNORE)
+                    .style()
+                    .width(Val::Px(24.0))
+                    .height(Val::Px(24.0))
+                    .background_color(slot.empire.color());
+                row._label(&ui_config, &description);
+
+                if session.is_host() && player != session.local {
+                    row._button(
+                        &ui_config,
+                        (
+                            "Kick",
+                            move |mut session: ResMut<NetSession>, mut lobby: ResMut<Lobby>| {
+                                lobby::kick(&mut session, &mut lobby, player);
+                            },
+                        ),
+                    );
+                }
+            })
+            .style()
+            .column_gap(Val::Px(16.0))
+            .align_items(AlignItems::Center);
+    }
+
+    builder._label(
+        &ui_config,
+        &format!(
+            "Galaxy: {} systems, density {}",
+            lobby.params.size, lobby.params.density
+        ),
+    );
+
+    // fill in the name once the lobby of the host arrived.
+    if let (Ok(mut input), Some(slot)) = (q_name.get_single_mut(), lobby.slot(session.local)) {
+        if input.value.is_empty() {
+            input.value = slot.empire.name.clone();
+        }
+    }
+}
+
+/// Send the empire name after it is edited.
+/// # Schedule
+/// [Update], in [MenuState::LobbyPage].
+pub fn update_empire_name(
+    mut session: ResMut<NetSession>,
+    mut lobby: ResMut<Lobby>,
+    q_name: Query<&TextInput, (With<EmpireNameInput>, Changed<TextInput>)>,
+) {
+    if let Ok(input) = q_name.get_single() {
+        if lobby
+            .slot(session.local)
+            .is_some_and(|slot| slot.empire.name != input.value && !input.value.is_empty())
+        {
+            lobby::update_local_slot(&mut session, &mut lobby, |slot| {
+                slot.empire.name = input.value.clone();
+            });
+        }
+    }
+}
+
+/// Go back to the online page once the session is closed.
+/// # Schedule
+/// [Update], in [MenuState::LobbyPage] without a [NetSession].
+pub fn leave_on_disconnect(mut state: ResMut<NextState<MenuState>>) {
+    state.set(MenuState::OnlineGamePage);
+}
+
+fn button_leave_action(mut commands: Commands, mut state: ResMut<NextState<MenuState>>) {
+    net::leave(&mut commands);
+    state.set(MenuState::OnlineGamePage);
+}
+
+fn button_start_action(
+    mut commands: Commands,
+    mut session: ResMut<NetSession>,
+    mut lobby: ResMut<Lobby>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    lobby::update_local_slot(&mut session, &mut lobby, |slot| slot.ready = true);
+
+    if !lobby.params.can_fill() {
+        warn!("Can not start, the galaxy is too dense to hold all its systems.");
+    } else if lobby.all_ready() {
+        net::start_game(&mut commands, &mut session, &mut app_state, &lobby);
+    } else {
+        warn!("Can not start, not all players are ready.");
+    }
+}
+
+fn button_ready_action(mut session: ResMut<NetSession>, lobby: Option<ResMut<Lobby>>) {
+    if let Some(mut lobby) = lobby {
+        lobby::update_local_slot(&mut session, &mut lobby, |slot| slot.ready = !slot.ready);
+    }
+}
+
+fn button_color_action(mut session: ResMut<NetSession>, lobby: Option<ResMut<Lobby>>) {
+    if let Some(mut lobby) = lobby {
+        lobby::update_local_slot(&mut session, &mut lobby, |slot| {
+            slot.empire.color = (slot.empire.color + 1) % EMPIRE_COLORS.len();
+        });
+    }
+}
+
+fn button_species_action(mut session: ResMut<NetSession>, lobby: Option<ResMut<Lobby>>) {
+    if let Some(mut lobby) = lobby {
+        lobby::update_local_slot(&mut session, &mut lobby, |slot| {
+            slot.empire.species = slot.empire.species.next();
+        });
+    }
+}
+
+fn button_smaller_action(mut lobby: ResMut<Lobby>) {
+    let size = (lobby.params.size / 2).max(*GalaxyGenParams::SIZE_RANGE.start());
+    lobby.params.size = size;
+}
+
+fn button_larger_action(mut lobby: ResMut<Lobby>) {
+    let size = (lobby.params.size * 2).min(*GalaxyGenParams::SIZE_RANGE.end());
+    lobby.params.size = size;
+}
+
+fn button_sparser_action(mut lobby: ResMut<Lobby>) {
+    let density = (lobby.params.density / 2.0).max(*GalaxyGenParams::DENSITY_RANGE.start());
+    lobby.params.density = density;
+}
+
+fn button_denser_action(mut lobby: ResMut<Lobby>) {
+    let density = (lobby.params.density * 2.0).min(*GalaxyGenParams::DENSITY_RANGE.end());
+    lobby.params.density = density;
+}
@@ -1,13 +1,36 @@
+mod load_game_page;
+mod lobby_page;
 mod new_game_page;
+mod online_game_page;
+mod settings_page;
+pub mod text_input;
 
-pub use super::{UiConfigs, UiCamera, MenuState};
+pub use super::{MenuState, UiCamera, UiConfigs};
+use crate::ui::audio::{click_sound, UiSound};
 use bevy::prelude::*;
 use bevy_mod_picking::{
     events::*,
-    prelude::{On, Pickable},
+    prelude::{Listener, On, Pickable},
+};
+pub use load_game_page::{
+    refresh as refresh_save_list, setup as setup_load_game_page, sort as sort_saves, SaveBrowser,
+};
+pub use lobby_page::{
+    leave_on_disconnect as leave_lobby_on_disconnect, refresh as refresh_lobby_page,
+    setup as setup_lobby_page, update_empire_name as update_lobby_empire_name,
+};
+pub use new_game_page::{
+    edit as edit_new_game, refresh_errors as refresh_new_game_errors, setup as setup_new_game_page,
+    NewGameForm,
+};
+pub use online_game_page::setup as setup_online_game_page;
+pub use settings_page::{
+    capture as capture_key_binding, edit as edit_settings,
+    refresh_bindings as refresh_key_bindings, reset_widgets as reset_settings_widgets,
+    setup as setup_settings_page, Draft, DraftReset, Rebinding,
 };
 use sickle_ui::prelude::{generated::*, UiBuilder, UiColumnExt, UiContainerExt, UiRoot, UiRowExt};
-pub use new_game_page::setup as setup_new_game_page;
+use text_input::TextInput;
 
 fn default_button_back_action(mut state: ResMut<NextState<MenuState>>) {
     state.set(MenuState::MainPage)
@@ -46,12 +69,17 @@ pub trait UiMenuPageExt: UiContainerExt + UiColumnExt {
                     bg_color.0 = ui_color_none;
                 },
             ))
-            .insert(
-                On::<Pointer<Over>>::target_component_mut::<BackgroundColor>(move |_, bg_color| {
-                    bg_color.0 = ui_color_focus;
-                }),
-            )
-            .insert(On::<Pointer<Click>>::run(content.1));
+            .insert(On::<Pointer<Over>>::run(
+                move |listener: Listener<Pointer<Over>>,
+                      mut q_color: Query<&mut BackgroundColor>,
+                      mut ew_sound: EventWriter<UiSound>| {
+                    if let Ok(mut bg_color) = q_color.get_mut(listener.target) {
+                        bg_color.0 = ui_color_focus;
+                    }
+                    ew_sound.send(UiSound::Hover);
+                },
+            ))
+            .insert(On::<Pointer<Click>>::run(click_sound.pipe(content.1)));
 
         builder
             .style()
@@ -66,6 +94,46 @@ pub trait UiMenuPageExt: UiContainerExt + UiColumnExt {
         builder
     }
 
+    fn _label(&mut self, cfg: &UiConfigs, text: &str) -> UiBuilder<'_, Entity> {
+        let text_style = TextStyle {
+            font: cfg.text.font.clone(),
+            font_size: 16.0 * cfg.scale * 1.5,
+            color: cfg.text.color,
+        };
+
+        let mut builder = self.spawn(TextBundle::from_section(text, text_style));
+        builder.insert(Pickable::IGNORE);
+        builder
+    }
+
+    fn _text_input(&mut self, cfg: &UiConfigs, value: &str) -> UiBuilder<'_, Entity> {
+        let text_style = TextStyle {
+            font: cfg.text.font.clone(),
+            font_size: 16.0 * cfg.scale * 1.5,
+            color: cfg.text.color,
+        };
+
+        let mut builder = self.container(ButtonBundle::default(), |parent| {
+            parent
+                .spawn(TextBundle::from_section(value, text_style))
+                .insert(Pickable::IGNORE);
+        });
+
+        builder
+            .insert(TextInput::new(value))
+            .insert(On::<Pointer<Click>>::run(text_input::focus));
+
+        builder
+            .style()
+            .width(Val::Px(320.0 * cfg.scale))
+            .padding(UiRect::all(Val::Px(4.0)))
+            .border(UiRect::all(Val::Px(1.0)))
+            .border_color(cfg.text.color)
+            .background_color(cfg.menu.color_none);
+
+        builder
+    }
+
     fn menu_page<S, T>(
         &mut self,
         cfg: &UiConfigs,
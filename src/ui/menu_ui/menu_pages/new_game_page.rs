@@ -1,16 +1,277 @@
+//! The new game page, where the galaxy and the empires are configured.
+//!
+//! The widgets edit the [NewGameForm], which is kept between visits.
+//! `Start` refuses a form with errors, which are listed below the widgets.
+
+use super::settings_page::setting;
+use super::text_input::TextInput;
 use super::{default_button_back_action, MenuState, UiCamera, UiConfigs, UiMenuPageExt};
 use crate::{
-    game_map::galaxy::{gen::GalaxyGenParams, Galaxy, PrimaryGalaxy},
+    command::PlayerId,
+    empire::{spawn_empires, Difficulty, EmpireSetup, Species, EMPIRE_COLORS, EMPIRE_COLOR_NAMES},
+    game_map::galaxy::{
+        gen::{GalaxyGenParams, GalaxyShape},
+        Galaxy, PrimaryGalaxy,
+    },
     states::LoadSource,
     ui::menu_ui::AppState,
+    utils::random_seed,
 };
+use bevy::color::palettes::tailwind;
 use bevy::prelude::*;
+use bevy_mod_picking::prelude::Pickable;
 use sickle_ui::prelude::*;
 
+/// The galaxy sizes to pick from, in planetary systems.
+const SIZES: [usize; 8] = [256, 512, 1024, 2048, 4096, 8192, 16384, 32768];
+
+/// The densities to pick from, in planetary systems per ly^2.
+const DENSITIES: [f32; 4] = [0.015625, 0.03125, 0.0625, 0.125];
+
+/// The most AI empires in a game, leaving a colour to each empire.
+const MAX_AI_EMPIRES: usize = EMPIRE_COLORS.len() - 1;
+
+/// The fewest planetary systems per empire for a game to have room.
+const MIN_SYSTEMS_PER_EMPIRE: usize = 64;
+
+/// The names of the AI empires, in order.
+const AI_NAMES: [&str; MAX_AI_EMPIRES] = [
+    "Velar Hegemony",
+    "Ossian Compact",
+    "Kith Dominion",
+    "Arrow Syndicate",
+    "Myrr Collective",
+    "Tauri Union",
+    "Zenth Republic",
+];
+
+/// The choices of the new game page.
+#[derive(Resource, Clone, Debug)]
+pub struct NewGameForm {
+    pub params: GalaxyGenParams,
+    /// the seed as typed, `params.seed` follows it while it is a number.
+    pub seed: String,
+    pub ai_empires: usize,
+    pub difficulty: Difficulty,
+    pub empire: EmpireSetup,
+}
+
+impl Default for NewGameForm {
+    fn default() -> Self {
+        let params = GalaxyGenParams::default();
+        Self {
+            seed: params.seed.to_string(),
+            params,
+            ai_empires: 3,
+            difficulty: Difficulty::default(),
+            empire: EmpireSetup::default(),
+        }
+    }
+}
+
+impl NewGameForm {
+    /// The reasons the game can not start with these choices.
+    pub fn errors(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+        if self.empire.name.trim().is_empty() {
+            errors.push("Your empire needs a name.".to_string());
+        }
+        if self.seed.trim().parse::<u128>().is_err() {
+            errors.push("The seed must be a positive whole number.".to_string());
+        }
+        if (1 + self.ai_empires) * MIN_SYSTEMS_PER_EMPIRE > self.params.size {
+            errors.push(format!(
+                "A galaxy of {} systems has room for {} empires at most.",
+                self.params.size,
+                self.params.size / MIN_SYSTEMS_PER_EMPIRE
+            ));
+        }
+        if !self.params.can_fill() {
+            errors.push(format!(
+                "A {} galaxy this dense can not hold all its systems.",
+                self.params.shape.name().to_lowercase()
+            ));
+        }
+        errors
+    }
+
+    /// The empires of the game, the player first. The AI empires take the
+    /// colours and species left over by the player.
+    fn empires(&self) -> impl Iterator<Item = (Option<PlayerId>, EmpireSetup)> + '_ {
+        let player_color = self.empire.color % EMPIRE_COLORS.len();
+        let colors = (0..EMPIRE_COLORS.len()).filter(move |c| *c != player_color);
+        let species = std::iter::successors(Some(self.empire.species.next()), |s| Some(s.next()));
+
+        let ais = colors.zip(species).zip(AI_NAMES).take(self.ai_empires).map(
+            |((color, species), name)| {
+                let setup = EmpireSetup {
+                    name: name.to_string(),
+                    color,
+                    species,
+                };
+                (None, setup)
+            },
+        );
+        std::iter::once((Some(PlayerId::default()), self.empire.clone())).chain(ais)
+    }
+}
+
+/// The seed of the galaxy.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct SeedInput;
+
+/// The name of the player empire.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct EmpireNameInput;
+
+/// The container of the errors of the form. Its content is rebuilt
+/// whenever the form changes.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct FormErrors;
+
+/// A dropdown bound to a choice of the [NewGameForm], by the index of the
+/// option.
+#[derive(Component, Copy, Clone)]
+pub struct FormField {
+    get: fn(&NewGameForm) -> Option<usize>,
+    set: fn(&mut NewGameForm, usize),
+}
+
+fn dropdown(
+    builder: &mut UiBuilder<Entity>,
+    ui_config: &UiConfigs,
+    form: &NewGameForm,
+    name: &str,
+    options: Vec<String>,
+    field: FormField,
+) {
+    let value = (field.get)(form);
+    setting(builder, ui_config, name, |parent| {
+        parent.dropdown(options, value).insert(field);
+    });
+}
+
+fn galaxy_column(column: &mut UiBuilder<Entity>, ui_config: &UiConfigs, form: &NewGameForm) {
+    column._label(ui_config, "Galaxy");
+    dropdown(
+        column,
+        ui_config,
+        form,
+        "Size",
+        SIZES.iter().map(|s| format!("{} systems", s)).collect(),
+        FormField {
+            get: |f| SIZES.iter().position(|s| *s == f.params.size),
+            set: |f, i| f.params.size = SIZES[i],
+        },
+    );
+    dropdown(
+        column,
+        ui_config,
+        form,
+        "Density",
+        DENSITIES
+            .iter()
+            .map(|d| format!("1 system per {} ly²", 1.0 / d))
+            .collect(),
+        FormField {
+            get: |f| DENSITIES.iter().position(|d| *d == f.params.density),
+            set: |f, i| f.params.density = DENSITIES[i],
+        },
+    );
+    dropdown(
+        column,
+        ui_config,
+        form,
+        "Shape",
+        GalaxyShape::ALL
+            .iter()
+            .map(|s| s.name().to_string())
+            .collect(),
+        FormField {
+            get: |f| GalaxyShape::ALL.iter().position(|s| *s == f.params.shape),
+            set: |f, i| f.params.shape = GalaxyShape::ALL[i],
+        },
+    );
+    setting(column, ui_config, "Seed", |parent| {
+        parent
+            ._text_input(ui_config, &form.seed)
+            // the longest seed has 39 digits.
+            .insert(TextInput {
+                value: form.seed.clone(),
+                max_len: 39,
+            })
+            .insert(SeedInput)
+            .style()
+            .width(Val::Percent(100.0));
+    });
+    column._button(ui_config, ("Randomize", button_randomize_action));
+}
+
+fn empires_column(column: &mut UiBuilder<Entity>, ui_config: &UiConfigs, form: &NewGameForm) {
+    column._label(ui_config, "Empires");
+    dropdown(
+        column,
+        ui_config,
+        form,
+        "AI Empires",
+        (0..=MAX_AI_EMPIRES).map(|n| n.to_string()).collect(),
+        FormField {
+            get: |f| (f.ai_empires <= MAX_AI_EMPIRES).then_some(f.ai_empires),
+            set: |f, i| f.ai_empires = i,
+        },
+    );
+    dropdown(
+        column,
+        ui_config,
+        form,
+        "Difficulty",
+        Difficulty::ALL
+            .iter()
+            .map(|d| d.name().to_string())
+            .collect(),
+        FormField {
+            get: |f| Difficulty::ALL.iter().position(|d| *d == f.difficulty),
+            set: |f, i| f.difficulty = Difficulty::ALL[i],
+        },
+    );
+
+    column._label(ui_config, "Your Empire");
+    setting(column, ui_config, "Name", |parent| {
+        parent
+            ._text_input(ui_config, &form.empire.name)
+            .insert(EmpireNameInput)
+            .style()
+            .width(Val::Percent(100.0));
+    });
+    dropdown(
+        column,
+        ui_config,
+        form,
+        "Species",
+        Species::ALL.iter().map(|s| s.name().to_string()).collect(),
+        FormField {
+            get: |f| Species::ALL.iter().position(|s| *s == f.empire.species),
+            set: |f, i| f.empire.species = Species::ALL[i],
+        },
+    );
+    dropdown(
+        column,
+        ui_config,
+        form,
+        "Colour",
+        EMPIRE_COLOR_NAMES.iter().map(|c| c.to_string()).collect(),
+        FormField {
+            get: |f| (f.empire.color < EMPIRE_COLORS.len()).then_some(f.empire.color),
+            set: |f, i| f.empire.color = i,
+        },
+    );
+}
+
 pub fn setup(
     mut commands: Commands,
     q_camera: Query<Entity, With<UiCamera>>,
     ui_config: Res<UiConfigs>,
+    form: Res<NewGameForm>,
 ) {
     let camera = q_camera.single();
 
@@ -19,22 +280,31 @@ pub fn setup(
         .menu_page(
             &ui_config,
             ("Return", default_button_back_action),
-            ("Start", button_next_action),
+            ("Start", button_start_action),
             |parent| {
                 parent
-                    .spawn(
-                        TextBundle::from_section(
-                            "Under Construction",
-                            TextStyle {
-                                font_size: 100.0,
-                                ..default()
-                            },
-                        )
-                        .with_text_justify(JustifyText::Center),
-                    )
+                    .column(|column| {
+                        column
+                            .row(|row| {
+                                row.column(|column| galaxy_column(column, &ui_config, &form))
+                                    .style()
+                                    .row_gap(Val::Px(8.0));
+                                row.column(|column| empires_column(column, &ui_config, &form))
+                                    .style()
+                                    .row_gap(Val::Px(8.0));
+                            })
+                            .style()
+                            .column_gap(Val::Px(64.0));
+
+                        column
+                            .column(|_| {})
+                            .insert(FormErrors)
+                            .style()
+                            .row_gap(Val::Px(8.0));
+                    })
                     .style()
-                    .align_self(AlignSelf::Center)
-                    .justify_self(JustifySelf::Center);
+                    .row_gap(Val::Px(32.0))
+                    .padding(UiRect::all(Val::Px(16.0)));
             },
         )
         .insert(TargetCamera(camera))
@@ -42,8 +312,99 @@ pub fn setup(
         .insert(StateScoped(MenuState::NewGamePage));
 }
 
-fn button_next_action(mut commands: Commands, mut app_state: ResMut<NextState<AppState>>) {
-    app_state.set(AppState::Loading(LoadSource::Generation));
+type FormInput<'a> = (&'a TextInput, Has<SeedInput>);
+type FormInputFilter = (
+    Changed<TextInput>,
+    Or<(With<SeedInput>, With<EmpireNameInput>)>,
+);
+
+/// Write the values of the widgets edited by the player into the
+/// [NewGameForm].
+/// # Schedule
+/// [Update], in [MenuState::NewGamePage].
+pub fn edit(
+    mut form: ResMut<NewGameForm>,
+    q_input: Query<FormInput, FormInputFilter>,
+    q_dropdown: Query<(&Dropdown, &FormField), Changed<Dropdown>>,
+) {
+    // only touch the form on actual edits, since it rebuilds the errors.
+    let edited = form.bypass_change_detection();
+    let mut changed = false;
+    for (input, is_seed) in q_input.iter() {
+        let value = match is_seed {
+            true => &mut edited.seed,
+            false => &mut edited.empire.name,
+        };
+        changed |= *value != input.value;
+        value.clone_from(&input.value);
+    }
+    if let Ok(seed) = edited.seed.trim().parse() {
+        edited.params.seed = seed;
+    }
+    for (dropdown, field) in q_dropdown.iter() {
+        let Some(index) = dropdown.value() else {
+            continue;
+        };
+        changed |= (field.get)(edited) != Some(index);
+        (field.set)(edited, index);
+    }
+    if changed {
+        form.set_changed();
+    }
+}
+
+/// List the errors of the form when it changes.
+/// # Schedule
+/// [Update], in [MenuState::NewGamePage], after [edit].
+pub fn refresh_errors(
+    mut commands: Commands,
+    ui_config: Res<UiConfigs>,
+    form: Res<NewGameForm>,
+    q_errors: Query<(Entity, Ref<FormErrors>)>,
+) {
+    let Ok((panel, marker)) = q_errors.get_single() else {
+        return;
+    };
+    if !marker.is_added() && !form.is_changed() {
+        return;
+    }
+
+    commands.entity(panel).despawn_descendants();
+    let error_style = TextStyle {
+        font: ui_config.text.font.clone(),
+        font_size: 16.0 * ui_config.scale * 1.5,
+        color: tailwind::RED_500.into(),
+    };
+    let mut builder = commands.ui_builder(panel);
+    for error in form.errors() {
+        builder
+            .spawn(TextBundle::from_section(error, error_style.clone()))
+            .insert(Pickable::IGNORE);
+    }
+}
+
+fn button_randomize_action(
+    mut form: ResMut<NewGameForm>,
+    mut q_seed: Query<&mut TextInput, With<SeedInput>>,
+) {
+    form.params.seed = random_seed();
+    form.seed = form.params.seed.to_string();
+    for mut input in q_seed.iter_mut() {
+        input.value.clone_from(&form.seed);
+    }
+}
+
+fn button_start_action(
+    mut commands: Commands,
+    form: Res<NewGameForm>,
+    mut app_state: ResMut<NextState<AppState>>,
+) {
+    if !form.errors().is_empty() {
+        return;
+    }
+    spawn_empires(&mut commands, form.params.seed, form.empires());
+    commands.insert_resource(form.difficulty);
     // entity for galaxy generation
-    commands.spawn((Galaxy, PrimaryGalaxy, GalaxyGenParams::default()));
+    commands.spawn((Galaxy, PrimaryGalaxy, form.params.clone()));
+    app_state.set(AppState::Loading(LoadSource::Generation));
 }
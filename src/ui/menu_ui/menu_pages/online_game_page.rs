@@ -1,42 +1,87 @@
-use super::{menu_page::MenuUiBuilderExt0, MenuState, UiCamera, UiConfigs};
+use super::text_input::TextInput;
+use super::{default_button_back_action, MenuState, UiCamera, UiConfigs, UiMenuPageExt};
+use crate::net;
 use bevy::prelude::*;
 use sickle_ui::prelude::*;
 
+/// The port to host a game on.
 #[derive(Component, Copy, Clone, Default, Debug)]
-pub struct OnlineMenu;
+struct HostPortInput;
 
-pub fn spawn_online_menu(
+/// The address of the game to join.
+#[derive(Component, Copy, Clone, Default, Debug)]
+struct JoinAddrInput;
+
+pub fn setup(
     mut commands: Commands,
     q_camera: Query<Entity, With<UiCamera>>,
-    ui_settings: Res<UiConfigs>,
+    ui_config: Res<UiConfigs>,
 ) {
     let camera = q_camera.single();
 
     commands
         .ui_builder(UiRoot)
-        .sub_menu_container(
-            &ui_settings,
-            "Start",
-            || {},
+        .menu_page(
+            &ui_config,
+            ("Return", default_button_back_action),
+            ("Join", button_join_action),
             |parent| {
                 parent
-                    .spawn(
-                        TextBundle::from_section(
-                            "Under Construction",
-                            TextStyle {
-                                font_size: 100.0,
-                                ..default()
-                            },
-                        )
-                        .with_text_justify(JustifyText::Center),
-                    )
+                    .column(|column| {
+                        column
+                            .row(|row| {
+                                row._label(&ui_config, "Port");
+                                row._text_input(&ui_config, "7777").insert(HostPortInput);
+                                row._button(&ui_config, ("Host", button_host_action));
+                            })
+                            .style()
+                            .column_gap(Val::Px(16.0))
+                            .align_items(AlignItems::Center);
+                        column
+                            .row(|row| {
+                                row._label(&ui_config, "Address");
+                                row._text_input(&ui_config, "127.0.0.1:7777")
+                                    .insert(JoinAddrInput);
+                            })
+                            .style()
+                            .column_gap(Val::Px(16.0))
+                            .align_items(AlignItems::Center);
+                    })
                     .style()
-                    .align_self(AlignSelf::Center)
-                    .justify_self(JustifySelf::Center);
+                    .row_gap(Val::Px(32.0))
+                    .align_self(AlignSelf::Center);
             },
         )
         .insert(TargetCamera(camera))
-        .insert(OnlineMenu)
         .insert(Name::new("Online Game Menu"))
         .insert(StateScoped(MenuState::OnlineGamePage));
 }
+
+fn button_host_action(
+    mut commands: Commands,
+    mut state: ResMut<NextState<MenuState>>,
+    q_port: Query<&TextInput, With<HostPortInput>>,
+) {
+    let Ok(port) = q_port.single().value.trim().parse() else {
+        error!("Invalid port {:?}", q_port.single().value);
+        return;
+    };
+
+    match net::host(&mut commands, port) {
+        Ok(()) => state.set(MenuState::LobbyPage),
+        Err(e) => error!("Fail to host on port {}: {}", port, e),
+    }
+}
+
+fn button_join_action(
+    mut commands: Commands,
+    mut state: ResMut<NextState<MenuState>>,
+    q_addr: Query<&TextInput, With<JoinAddrInput>>,
+) {
+    let addr = q_addr.single().value.trim();
+
+    match net::join(&mut commands, addr) {
+        Ok(()) => state.set(MenuState::LobbyPage),
+        Err(e) => error!("Fail to join {}: {}", addr, e),
+    }
+}
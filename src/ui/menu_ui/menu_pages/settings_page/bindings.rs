@@ -0,0 +1,105 @@
+//! The key bindings of the input tab.
+
+use super::{Draft, UiConfigs, UiMenuPageExt};
+use crate::ui::settings::{key_name, Action, KeyBindings};
+use bevy::color::palettes::tailwind;
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::Pickable;
+use sickle_ui::prelude::*;
+
+/// The container of the key bindings. Its content is rebuilt whenever the
+/// bindings change.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct BindingsPanel;
+
+/// The binding slot waiting for a key press, if any. `Delete` unbinds it.
+#[derive(Resource, Copy, Clone, Default, Debug)]
+pub struct Rebinding(pub Option<(Action, usize)>);
+
+/// Rebuild the key bindings when they, or the slot being rebound, change.
+/// # Schedule
+/// [Update], in [super::MenuState::SettingsPage].
+pub fn refresh(
+    mut commands: Commands,
+    mut shown: Local<Option<KeyBindings>>,
+    ui_config: Res<UiConfigs>,
+    draft: Res<Draft>,
+    rebinding: Res<Rebinding>,
+    q_panel: Query<(Entity, Ref<BindingsPanel>)>,
+) {
+    let Ok((panel, marker)) = q_panel.get_single() else {
+        return;
+    };
+    let bindings = &draft.0.input.bindings;
+    if !marker.is_added() && !rebinding.is_changed() && shown.as_ref() == Some(bindings) {
+        return;
+    }
+    *shown = Some(bindings.clone());
+
+    commands.entity(panel).despawn_descendants();
+    let mut builder = commands.ui_builder(panel);
+
+    let conflicts = bindings.conflicts();
+    for action in Action::ALL {
+        builder
+            .row(|row| {
+                row._label(&ui_config, action.name())
+                    .style()
+                    .width(Val::Px(240.0 * ui_config.scale));
+
+                for (slot, key) in bindings.keys(action).into_iter().enumerate() {
+                    let text = match (rebinding.0 == Some((action, slot)), key) {
+                        (true, _) => "...".to_string(),
+                        (false, Some(key)) => key_name(key),
+                        (false, None) => "-".to_string(),
+                    };
+                    let mut button = row._button(
+                        &ui_config,
+                        (&text, move |mut rebinding: ResMut<Rebinding>| {
+                            rebinding.0 = match rebinding.0 {
+                                Some(current) if current == (action, slot) => None,
+                                _ => Some((action, slot)),
+                            };
+                        }),
+                    );
+                    if key.is_some_and(|key| conflicts.iter().any(|(k, _)| *k == key)) {
+                        button.style().border_color(tailwind::RED_500.into());
+                    }
+                }
+            })
+            .style()
+            .column_gap(Val::Px(16.0))
+            .align_items(AlignItems::Center);
+    }
+
+    let warning_style = TextStyle {
+        font: ui_config.text.font.clone(),
+        font_size: 16.0 * ui_config.scale * 1.5,
+        color: tailwind::RED_500.into(),
+    };
+    for (key, actions) in conflicts {
+        let actions: Vec<_> = actions.iter().map(Action::name).collect();
+        builder
+            .spawn(TextBundle::from_section(
+                format!("{} is bound to {}", key_name(key), actions.join(", ")),
+                warning_style.clone(),
+            ))
+            .insert(Pickable::IGNORE);
+    }
+}
+
+/// Bind the next pressed key to the slot being rebound.
+/// # Schedule
+/// [Update], in [super::MenuState::SettingsPage].
+pub fn capture(
+    keys: Res<ButtonInput<KeyCode>>,
+    mut rebinding: ResMut<Rebinding>,
+    mut draft: ResMut<Draft>,
+) {
+    let (Some((action, slot)), Some(key)) = (rebinding.0, keys.get_just_pressed().next()) else {
+        return;
+    };
+    let key = (*key != KeyCode::Delete).then_some(*key);
+    draft.0.input.bindings.bind(action, slot, key);
+    rebinding.0 = None;
+}
@@ -0,0 +1,477 @@
+//! The settings page, with a tab for each group of settings.
+//!
+//! The widgets edit a [Draft] of the settings. `Apply` makes the draft the
+//! current settings and saves them, `Revert` goes back to the current
+//! settings and `Defaults` to the default ones.
+
+mod bindings;
+
+pub use bindings::{capture, refresh as refresh_bindings, Rebinding};
+
+use super::{default_button_back_action, MenuState, UiCamera, UiConfigs, UiMenuPageExt};
+use crate::ui::settings::{
+    AudioSettings, DisplayMode, EdgeScrollMode, InputSettings, SettingsFile, SettingsStorage,
+    SpeedCurve, Tonemapper, VideoSettings, RESOLUTIONS, SETTINGS_VERSION,
+};
+use bevy::prelude::*;
+use bindings::BindingsPanel;
+use sickle_ui::prelude::*;
+
+/// The settings being edited.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct Draft(pub SettingsFile);
+
+/// Sent when the [Draft] is replaced, for the widgets to show its values.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct DraftReset;
+
+/// A slider bound to a number of the [Draft].
+#[derive(Component, Copy, Clone)]
+pub struct SliderField(fn(&mut SettingsFile) -> &mut f32);
+
+/// A checkbox bound to a flag of the [Draft].
+#[derive(Component, Copy, Clone)]
+pub struct CheckboxField(fn(&mut SettingsFile) -> &mut bool);
+
+/// A dropdown bound to a choice of the [Draft], by the index of the
+/// option.
+#[derive(Component, Copy, Clone)]
+pub struct DropdownField {
+    get: fn(&SettingsFile) -> Option<usize>,
+    set: fn(&mut SettingsFile, usize),
+}
+
+/// The current settings.
+fn current(input: &InputSettings, video: &VideoSettings, audio: &AudioSettings) -> SettingsFile {
+    SettingsFile {
+        version: SETTINGS_VERSION,
+        audio: *audio,
+        video: *video,
+        input: input.clone(),
+    }
+}
+
+/// A row with the name of a setting and the widget editing it.
+pub(super) fn setting(
+    builder: &mut UiBuilder<Entity>,
+    ui_config: &UiConfigs,
+    name: &str,
+    spawn_widget: impl FnOnce(&mut UiBuilder<Entity>),
+) {
+    builder
+        .row(|row| {
+            row._label(ui_config, name)
+                .style()
+                .width(Val::Px(240.0 * ui_config.scale));
+            row.column(spawn_widget)
+                .style()
+                .width(Val::Px(320.0 * ui_config.scale));
+        })
+        .style()
+        .column_gap(Val::Px(16.0))
+        .align_items(AlignItems::Center);
+}
+
+fn slider(
+    builder: &mut UiBuilder<Entity>,
+    ui_config: &UiConfigs,
+    file: &mut SettingsFile,
+    name: &str,
+    (min, max): (f32, f32),
+    field: fn(&mut SettingsFile) -> &mut f32,
+) {
+    let value = *field(file);
+    setting(builder, ui_config, name, |parent| {
+        parent
+            .slider(SliderConfig::horizontal(
+                None::<String>,
+                min,
+                max,
+                value,
+                true,
+            ))
+            .insert(SliderField(field));
+    });
+}
+
+fn checkbox(
+    builder: &mut UiBuilder<Entity>,
+    ui_config: &UiConfigs,
+    file: &mut SettingsFile,
+    name: &str,
+    field: fn(&mut SettingsFile) -> &mut bool,
+) {
+    let value = *field(file);
+    setting(builder, ui_config, name, |parent| {
+        parent
+            .checkbox(None::<String>, value)
+            .insert(CheckboxField(field));
+    });
+}
+
+fn dropdown(
+    builder: &mut UiBuilder<Entity>,
+    ui_config: &UiConfigs,
+    file: &SettingsFile,
+    name: &str,
+    options: Vec<String>,
+    field: DropdownField,
+) {
+    let value = (field.get)(file);
+    setting(builder, ui_config, name, |parent| {
+        parent.dropdown(options, value).insert(field);
+    });
+}
+
+/// The sliders of the input tab, next to the key bindings.
+fn input_tab(panel: &mut UiBuilder<Entity>, ui_config: &UiConfigs, file: &mut SettingsFile) {
+    panel
+        .row(|row| {
+            row.column(|column| {
+                slider(
+                    column,
+                    ui_config,
+                    file,
+                    "Mouse Sensitivity",
+                    (0.1, 3.0),
+                    |f| &mut f.input.mouse_motion_sensitivity,
+                );
+                slider(
+                    column,
+                    ui_config,
+                    file,
+                    "Scroll Sensitivity",
+                    (0.1, 3.0),
+                    |f| &mut f.input.mouse_scroll_sensitivity,
+                );
+                slider(
+                    column,
+                    ui_config,
+                    file,
+                    "Double-Click Time",
+                    (0.1, 1.0),
+                    |f| &mut f.input.double_click_time,
+                );
+                slider(
+                    column,
+                    ui_config,
+                    file,
+                    "Drag Threshold",
+                    (1.0, 16.0),
+                    |f| &mut f.input.drag_threshold,
+                );
+                slider(
+                    column,
+                    ui_config,
+                    file,
+                    "Gamepad Pointer",
+                    (200.0, 2000.0),
+                    |f| &mut f.input.gamepad_cursor_speed,
+                );
+            })
+            .style()
+            .row_gap(Val::Px(8.0));
+
+            row.column(|column| {
+                column._label(ui_config, "Key Bindings");
+                column
+                    .column(|_| {})
+                    .insert(BindingsPanel)
+                    .style()
+                    .row_gap(Val::Px(8.0));
+            })
+            .style()
+            .row_gap(Val::Px(16.0));
+        })
+        .style()
+        .column_gap(Val::Px(64.0));
+}
+
+fn video_tab(panel: &mut UiBuilder<Entity>, ui_config: &UiConfigs, file: &mut SettingsFile) {
+    dropdown(
+        panel,
+        ui_config,
+        file,
+        "Window Mode",
+        DisplayMode::ALL
+            .iter()
+            .map(|m| m.name().to_string())
+            .collect(),
+        DropdownField {
+            get: |f| {
+                DisplayMode::ALL
+                    .iter()
+                    .position(|m| *m == f.video.display_mode)
+            },
+            set: |f, i| f.video.display_mode = DisplayMode::ALL[i],
+        },
+    );
+    dropdown(
+        panel,
+        ui_config,
+        file,
+        "Resolution",
+        RESOLUTIONS
+            .iter()
+            .map(|r| format!("{} x {}", r.x, r.y))
+            .collect(),
+        DropdownField {
+            get: |f| RESOLUTIONS.iter().position(|r| *r == f.video.resolution),
+            set: |f, i| f.video.resolution = RESOLUTIONS[i],
+        },
+    );
+    checkbox(panel, ui_config, file, "VSync", |f| &mut f.video.vsync);
+    slider(panel, ui_config, file, "Bloom", (0.0, 0.5), |f| {
+        &mut f.video.bloom_intensity
+    });
+    dropdown(
+        panel,
+        ui_config,
+        file,
+        "Tonemapping",
+        Tonemapper::ALL
+            .iter()
+            .map(|t| t.name().to_string())
+            .collect(),
+        DropdownField {
+            get: |f| {
+                Tonemapper::ALL
+                    .iter()
+                    .position(|t| *t == f.video.tonemapper)
+            },
+            set: |f, i| f.video.tonemapper = Tonemapper::ALL[i],
+        },
+    );
+    slider(panel, ui_config, file, "UI Scale", (0.5, 2.0), |f| {
+        &mut f.video.ui_scale
+    });
+}
+
+fn audio_tab(panel: &mut UiBuilder<Entity>, ui_config: &UiConfigs, file: &mut SettingsFile) {
+    slider(panel, ui_config, file, "Master Volume", (0.0, 1.0), |f| {
+        &mut f.audio.master
+    });
+    slider(panel, ui_config, file, "Music", (0.0, 1.0), |f| {
+        &mut f.audio.music
+    });
+    slider(panel, ui_config, file, "Effects", (0.0, 1.0), |f| {
+        &mut f.audio.effects
+    });
+    slider(panel, ui_config, file, "Interface", (0.0, 1.0), |f| {
+        &mut f.audio.ui
+    });
+}
+
+/// The camera behavior, for now the edge scrolling.
+fn gameplay_tab(panel: &mut UiBuilder<Entity>, ui_config: &UiConfigs, file: &mut SettingsFile) {
+    dropdown(
+        panel,
+        ui_config,
+        file,
+        "Edge Scrolling",
+        EdgeScrollMode::ALL
+            .iter()
+            .map(|m| m.name().to_string())
+            .collect(),
+        DropdownField {
+            get: |f| {
+                let mode = f.input.edge_scroll.mode;
+                EdgeScrollMode::ALL.iter().position(|m| *m == mode)
+            },
+            set: |f, i| f.input.edge_scroll.mode = EdgeScrollMode::ALL[i],
+        },
+    );
+    dropdown(
+        panel,
+        ui_config,
+        file,
+        "Speed Curve",
+        SpeedCurve::ALL
+            .iter()
+            .map(|c| c.name().to_string())
+            .collect(),
+        DropdownField {
+            get: |f| {
+                let curve = f.input.edge_scroll.curve;
+                SpeedCurve::ALL.iter().position(|c| *c == curve)
+            },
+            set: |f, i| f.input.edge_scroll.curve = SpeedCurve::ALL[i],
+        },
+    );
+    slider(panel, ui_config, file, "Border Width", (0.0, 64.0), |f| {
+        &mut f.input.edge_scroll.border_width
+    });
+    slider(
+        panel,
+        ui_config,
+        file,
+        "Scroll Speed",
+        (25.0, 1000.0),
+        |f| &mut f.input.edge_scroll.base_speed,
+    );
+    slider(panel, ui_config, file, "Height Factor", (0.0, 4.0), |f| {
+        &mut f.input.edge_scroll.height_factor
+    });
+    slider(panel, ui_config, file, "Acceleration", (0.0, 2.0), |f| {
+        &mut f.input.edge_scroll.acceleration_time
+    });
+}
+
+pub fn setup(
+    mut commands: Commands,
+    q_camera: Query<Entity, With<UiCamera>>,
+    ui_config: Res<UiConfigs>,
+    input: Res<InputSettings>,
+    video: Res<VideoSettings>,
+    audio: Res<AudioSettings>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    let camera = q_camera.single();
+    rebinding.0 = None;
+    let mut file = current(&input, &video, &audio);
+    commands.insert_resource(Draft(file.clone()));
+
+    commands
+        .ui_builder(UiRoot)
+        .menu_page(
+            &ui_config,
+            ("Return", default_button_back_action),
+            ("Apply", button_apply_action),
+            |parent| {
+                parent
+                    .column(|column| {
+                        column
+                            .row(|row| {
+                                row._button(&ui_config, ("Revert", button_revert_action));
+                                row._button(&ui_config, ("Defaults", button_defaults_action));
+                            })
+                            .style()
+                            .column_gap(Val::Px(16.0))
+                            .justify_content(JustifyContent::FlexEnd);
+
+                        column.tab_container(|tabs| {
+                            let tab_style = |panel: &mut UiBuilder<Entity>| {
+                                panel
+                                    .style()
+                                    .row_gap(Val::Px(8.0))
+                                    .padding(UiRect::all(Val::Px(16.0)));
+                            };
+                            tabs.add_tab("Input".into(), |panel| {
+                                input_tab(panel, &ui_config, &mut file);
+                                tab_style(panel);
+                            });
+                            tabs.add_tab("Video".into(), |panel| {
+                                video_tab(panel, &ui_config, &mut file);
+                                tab_style(panel);
+                            });
+                            tabs.add_tab("Audio".into(), |panel| {
+                                audio_tab(panel, &ui_config, &mut file);
+                                tab_style(panel);
+                            });
+                            tabs.add_tab("Gameplay".into(), |panel| {
+                                gameplay_tab(panel, &ui_config, &mut file);
+                                tab_style(panel);
+                            });
+                        });
+                    })
+                    .style()
+                    .width(Val::Percent(100.0))
+                    .row_gap(Val::Px(16.0))
+                    .padding(UiRect::all(Val::Px(16.0)));
+            },
+        )
+        .insert(TargetCamera(camera))
+        .insert(Name::new("Settings Menu"))
+        .insert(StateScoped(MenuState::SettingsPage));
+}
+
+/// Write the values of the widgets edited by the player into the [Draft].
+/// # Schedule
+/// [Update], in [MenuState::SettingsPage].
+pub fn edit(
+    mut draft: ResMut<Draft>,
+    q_slider: Query<(&Slider, &SliderField), Changed<Slider>>,
+    q_checkbox: Query<(&Checkbox, &CheckboxField), Changed<Checkbox>>,
+    q_dropdown: Query<(&Dropdown, &DropdownField), Changed<Dropdown>>,
+) {
+    // only touch the draft on actual edits, since it rebuilds the bindings.
+    let file = &mut draft.bypass_change_detection().0;
+    let mut changed = false;
+    for (slider, field) in q_slider.iter() {
+        let value = (field.0)(file);
+        changed |= *value != slider.value();
+        *value = slider.value();
+    }
+    for (checkbox, field) in q_checkbox.iter() {
+        let value = (field.0)(file);
+        changed |= *value != checkbox.checked;
+        *value = checkbox.checked;
+    }
+    for (dropdown, field) in q_dropdown.iter() {
+        let Some(index) = dropdown.value() else {
+            continue;
+        };
+        changed |= (field.get)(file) != Some(index);
+        (field.set)(file, index);
+    }
+    if changed {
+        draft.set_changed();
+    }
+}
+
+/// Show the values of the [Draft] once it is replaced.
+/// # Schedule
+/// [Update], in [MenuState::SettingsPage], on [DraftReset].
+pub fn reset_widgets(
+    mut draft: ResMut<Draft>,
+    mut q_slider: Query<(&mut Slider, &SliderField)>,
+    mut q_checkbox: Query<(&mut Checkbox, &CheckboxField)>,
+    mut q_dropdown: Query<(&mut Dropdown, &DropdownField)>,
+) {
+    let file = &mut draft.bypass_change_detection().0;
+    for (mut slider, field) in q_slider.iter_mut() {
+        slider.set_value(*(field.0)(file));
+    }
+    for (mut checkbox, field) in q_checkbox.iter_mut() {
+        checkbox.checked = *(field.0)(file);
+    }
+    for (mut dropdown, field) in q_dropdown.iter_mut() {
+        dropdown.set_value((field.get)(file));
+    }
+}
+
+fn button_apply_action(
+    draft: Res<Draft>,
+    mut storage: ResMut<SettingsStorage>,
+    mut input: ResMut<InputSettings>,
+    mut video: ResMut<VideoSettings>,
+    mut audio: ResMut<AudioSettings>,
+) {
+    input.set_if_neq(draft.0.input.clone());
+    video.set_if_neq(draft.0.video);
+    audio.set_if_neq(draft.0.audio);
+    storage.save(&draft.0);
+}
+
+fn button_revert_action(
+    mut ew_reset: EventWriter<DraftReset>,
+    mut draft: ResMut<Draft>,
+    mut rebinding: ResMut<Rebinding>,
+    input: Res<InputSettings>,
+    video: Res<VideoSettings>,
+    audio: Res<AudioSettings>,
+) {
+    draft.0 = current(&input, &video, &audio);
+    rebinding.0 = None;
+    ew_reset.send(DraftReset);
+}
+
+fn button_defaults_action(
+    mut ew_reset: EventWriter<DraftReset>,
+    mut draft: ResMut<Draft>,
+    mut rebinding: ResMut<Rebinding>,
+) {
+    draft.0 = SettingsFile::default();
+    rebinding.0 = None;
+    ew_reset.send(DraftReset);
+}
@@ -0,0 +1,89 @@
+//! A single-line text input for menu pages.
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::input::ButtonState;
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::{Click, Listener, Pointer};
+
+/// The value of a text input. Its first text child shows the value.
+#[derive(Component, Clone, Debug)]
+pub struct TextInput {
+    pub value: String,
+    pub max_len: usize,
+}
+
+impl TextInput {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            max_len: 32,
+        }
+    }
+}
+
+/// The [TextInput] receiving the keyboard input, if any.
+#[derive(Resource, Copy, Clone, Default, Debug)]
+pub struct TextInputFocus(pub Option<Entity>);
+
+/// Give the focus to the clicked input.
+pub(super) fn focus(listener: Listener<Pointer<Click>>, mut focus: ResMut<TextInputFocus>) {
+    focus.0 = Some(listener.listener());
+}
+
+/// Edit the focused input from keyboard events.
+/// # Schedule
+/// [Update], in [crate::AppState::InMenu].
+pub fn typing(
+    mut er_key: EventReader<KeyboardInput>,
+    mut focus: ResMut<TextInputFocus>,
+    mut q_input: Query<&mut TextInput>,
+) {
+    let Some(mut input) = focus.0.and_then(|entity| q_input.get_mut(entity).ok()) else {
+        er_key.clear();
+        return;
+    };
+
+    for event in er_key.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+        match &event.logical_key {
+            Key::Character(c) if input.value.chars().count() < input.max_len => {
+                input.value.push_str(c);
+            }
+            Key::Space if input.value.chars().count() < input.max_len => {
+                input.value.push(' ');
+            }
+            Key::Backspace => {
+                input.value.pop();
+            }
+            Key::Enter | Key::Escape => {
+                focus.0 = None;
+                break;
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Show the value of each [TextInput], with a caret if it is focused.
+/// # Schedule
+/// [Update], in [crate::AppState::InMenu], after [typing].
+pub fn sync(
+    focus: Res<TextInputFocus>,
+    q_input: Query<(Entity, Ref<TextInput>, &Children)>,
+    mut q_text: Query<&mut Text>,
+) {
+    for (entity, input, children) in q_input.iter() {
+        if !input.is_changed() && !focus.is_changed() {
+            continue;
+        }
+
+        let caret = if focus.0 == Some(entity) { "_" } else { "" };
+        if let Some(child) = children.iter().find(|c| q_text.contains(**c)) {
+            if let Ok(mut text) = q_text.get_mut(*child) {
+                text.sections[0].value = format!("{}{}", input.value, caret);
+            }
+        }
+    }
+}
@@ -1,6 +1,8 @@
 //! The menu UI.
 
 mod configs;
+mod focus;
+mod loading_screen;
 mod main_page;
 mod menu_pages;
 mod wallpaper;
@@ -8,10 +10,14 @@ mod wallpaper;
 pub use crate::ui::camera::PrimaryCamera as UiCamera;
 pub use configs::Configs as UiConfigs;
 
+use crate::game_map::gen::LoadingProgress;
+use crate::net::{Lobby, NetSession};
 use crate::states::AppStateLoading;
+use crate::ui::settings::VideoSettings;
 use crate::AppState;
 use bevy::prelude::*;
 use bevy::time::common_conditions::on_timer;
+use menu_pages::text_input::{self, TextInputFocus};
 use std::time::Duration;
 
 /// The sub-state for menu page management.
@@ -24,6 +30,7 @@ pub enum MenuState {
     LoadGamePage,
     SettingsPage,
     OnlineGamePage,
+    LobbyPage,
 }
 
 /// the plugin for setup and manage menu.
@@ -34,6 +41,17 @@ impl Plugin for InMenuPlugin {
         app.add_sub_state::<MenuState>()
             .enable_state_scoped_entities::<MenuState>()
             .insert_resource(configs::Configs::default())
+            .add_systems(
+                PreUpdate,
+                configs::sync_scale.run_if(resource_changed::<VideoSettings>),
+            )
+            .add_systems(
+                Update,
+                configs::rebuild_page
+                    .run_if(resource_changed::<configs::Configs>)
+                    .run_if(not(resource_added::<configs::Configs>))
+                    .run_if(in_state(AppState::InMenu)),
+            )
             .add_systems(OnEnter(AppState::InMenu), wallpaper::setup)
             .add_systems(OnExit(AppStateLoading), wallpaper::cleanup)
             .add_systems(
@@ -41,13 +59,86 @@ impl Plugin for InMenuPlugin {
                 wallpaper::update
                     .run_if(on_timer(Duration::from_secs(10)).and_then(in_state(AppStateLoading))),
             )
+            .enable_state_scoped_entities::<AppStateLoading>()
+            .add_systems(OnEnter(AppStateLoading), loading_screen::setup)
+            .add_systems(
+                Update,
+                (
+                    loading_screen::update.run_if(resource_changed::<LoadingProgress>),
+                    loading_screen::next_tip.run_if(on_timer(Duration::from_secs(6))),
+                )
+                    .run_if(in_state(AppStateLoading)),
+            )
             .add_systems(OnEnter(MenuState::MainPage), main_page::setup)
+            .init_resource::<menu_pages::NewGameForm>()
             .add_systems(
                 OnEnter(MenuState::NewGamePage),
                 menu_pages::setup_new_game_page,
+            )
+            .add_systems(
+                Update,
+                (
+                    menu_pages::edit_new_game,
+                    menu_pages::refresh_new_game_errors,
+                )
+                    .chain()
+                    .run_if(in_state(MenuState::NewGamePage)),
+            )
+            .init_resource::<menu_pages::SaveBrowser>()
+            .add_systems(
+                OnEnter(MenuState::LoadGamePage),
+                menu_pages::setup_load_game_page,
+            )
+            .add_systems(
+                Update,
+                (menu_pages::sort_saves, menu_pages::refresh_save_list)
+                    .chain()
+                    .run_if(in_state(MenuState::LoadGamePage)),
+            )
+            .add_systems(
+                OnEnter(MenuState::OnlineGamePage),
+                menu_pages::setup_online_game_page,
+            )
+            .add_systems(OnEnter(MenuState::LobbyPage), menu_pages::setup_lobby_page)
+            .add_systems(
+                Update,
+                (
+                    menu_pages::refresh_lobby_page,
+                    menu_pages::update_lobby_empire_name
+                        .run_if(resource_exists::<NetSession>.and_then(resource_exists::<Lobby>)),
+                    menu_pages::leave_lobby_on_disconnect
+                        .run_if(not(resource_exists::<NetSession>)),
+                )
+                    .run_if(in_state(MenuState::LobbyPage)),
+            )
+            .init_resource::<menu_pages::Rebinding>()
+            .init_resource::<menu_pages::Draft>()
+            .add_event::<menu_pages::DraftReset>()
+            .add_systems(
+                OnEnter(MenuState::SettingsPage),
+                menu_pages::setup_settings_page,
+            )
+            .add_systems(
+                Update,
+                (
+                    menu_pages::capture_key_binding,
+                    menu_pages::edit_settings,
+                    menu_pages::reset_settings_widgets.run_if(on_event::<menu_pages::DraftReset>()),
+                    menu_pages::refresh_key_bindings,
+                )
+                    .chain()
+                    .run_if(in_state(MenuState::SettingsPage)),
+            )
+            .init_resource::<TextInputFocus>()
+            .add_systems(
+                Update,
+                (
+                    (text_input::typing, text_input::sync).chain(),
+                    (focus::navigate, focus::activate)
+                        .before(text_input::typing)
+                        .before(menu_pages::capture_key_binding),
+                )
+                    .run_if(in_state(AppState::InMenu)),
             );
-        // .add_systems(OnEnter(MenuState::LoadGamePage), spawn_load_game_menu)
-        // .add_systems(OnEnter(MenuState::OnlineGamePage), spawn_online_menu)
-        // .add_systems(OnEnter(MenuState::SettingsPage), spawn_settings_menu);
     }
 }
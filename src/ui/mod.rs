@@ -1,10 +1,17 @@
 //! UI-related codes, including menu UI, game UI and camera setup.
 
+pub mod audio;
 pub mod camera;
+pub mod game_ui;
 mod menu_ui;
 mod settings;
-mod input;
+pub mod input;
+mod quick_save;
+pub mod selection;
 
+use crate::fleet;
+use crate::game_map::planetary_system;
+use crate::AppState;
 use bevy::prelude::*;
 pub use camera::setup;
 pub use camera::PrimaryCamera;
@@ -14,8 +21,33 @@ pub struct UserInterfacePlugin;
 impl Plugin for UserInterfacePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(settings::SettingsPlugin)
+            .add_plugins(audio::SoundPlugin)
             .add_plugins(menu_ui::InMenuPlugin)
+            .add_plugins(game_ui::InGamePlugin)
             .add_plugins(input::InputPlugin)
-            .add_plugins(camera::primary_camera::PrimaryCameraPlugin);
+            .add_plugins(camera::primary_camera::PrimaryCameraPlugin)
+            .init_resource::<selection::Selection>()
+            .add_event::<selection::SelectionChanged>()
+            .add_systems(
+                Update,
+                (
+                    selection::cleanup,
+                    selection::click,
+                    selection::drag,
+                    selection::order_move,
+                    selection::highlight,
+                )
+                    .chain()
+                    .run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(
+                Update,
+                quick_save::quick_save.run_if(in_state(AppState::InGame)),
+            )
+            .add_systems(OnExit(AppState::InGame), selection::reset)
+            .add_systems(
+                Update,
+                (planetary_system::spawn_visuals, fleet::spawn_visuals),
+            );
     }
 }
@@ -0,0 +1,50 @@
+//! Saving the running game from the keyboard.
+
+use super::settings::{Action, InputSettings};
+use crate::command::PlayerId;
+use crate::net::NetSession;
+use crate::save::{saves_dir, SaveFile, SaveQuery};
+use bevy::prelude::*;
+use bevy::render::view::screenshot::ScreenshotManager;
+use bevy::window::PrimaryWindow;
+
+/// Save the game on [Action::QuickSave], with a screenshot of the window as
+/// its thumbnail.
+/// # Schedule
+/// [Update], in [crate::AppState::InGame].
+pub fn quick_save(
+    keys: Res<ButtonInput<KeyCode>>,
+    settings: Res<InputSettings>,
+    save: SaveQuery,
+    session: Option<Res<NetSession>>,
+    q_window: Query<Entity, With<PrimaryWindow>>,
+    mut screenshots: ResMut<ScreenshotManager>,
+) {
+    if !settings.bindings.just_pressed(Action::QuickSave, &keys) {
+        return;
+    }
+    let Some(dir) = saves_dir() else {
+        warn!("Fail to find the data directory, the game is not saved.");
+        return;
+    };
+    let Some(game) = save.save() else {
+        return;
+    };
+
+    let player = session.map_or(PlayerId::default(), |session| session.local);
+    let path = match SaveFile::new(game, player).write(&dir) {
+        Ok(path) => path,
+        Err(error) => {
+            warn!("Fail to save the game into {:?}: {}", dir, error);
+            return;
+        }
+    };
+    info!("Game saved to {:?}.", path);
+
+    if let Ok(window) = q_window.get_single() {
+        if let Err(error) = screenshots.save_screenshot_to_disk(window, path.with_extension("png"))
+        {
+            warn!("Fail to take the thumbnail of {:?}: {}", path, error);
+        }
+    }
+}
@@ -0,0 +1,273 @@
+//! The objects selected by the player.
+//!
+//! - a left click on a planetary system or a fleet selects it, a left click
+//!     on nothing clears the selection.
+//! - a left drag draws a rectangle, and selects all fleets inside it,
+//!     unless it starts on the UI.
+//! - with `Shift` held, both add to the selection instead.
+//! - a right click on a planetary system orders the selected fleets of the
+//!     player to move there.
+
+use crate::command::{GameCommand, PendingCommands, PlayerId};
+use crate::empire::{Empire, PlayerControlled};
+use crate::fleet::{Fleet, Owner};
+use crate::game_map::planetary_system::PlanetarySystem;
+use crate::net::NetSession;
+use crate::ui::audio::Effect;
+use crate::ui::input::{MouseButtons, MouseMotion};
+use crate::ui::PrimaryCamera;
+use crate::utils::{ObjectId, ObjectRef};
+use bevy::color::palettes::tailwind;
+use bevy::prelude::*;
+use bevy_mod_picking::prelude::{Click, Down, Pickable, Pointer, PointerButton};
+
+/// The objects selected by the player, in the order they were selected.
+#[derive(Resource, Clone, Default, Debug)]
+pub struct Selection(pub Vec<ObjectRef>);
+
+impl Selection {
+    pub fn contains(&self, object: ObjectRef) -> bool {
+        self.0.contains(&object)
+    }
+
+    /// Select the objects, after the current ones if `add` is set. Return
+    /// if the selection changed.
+    pub fn select(&mut self, objects: impl IntoIterator<Item = ObjectRef>, add: bool) -> bool {
+        let old = self.0.len();
+        let mut changed = false;
+        if !add {
+            changed = !self.0.is_empty();
+            self.0.clear();
+        }
+        for object in objects {
+            if !self.0.contains(&object) {
+                self.0.push(object);
+            }
+        }
+        changed || self.0.len() != old
+    }
+}
+
+/// Sent whenever the [Selection] changes.
+#[derive(Event, Copy, Clone, Debug)]
+pub struct SelectionChanged;
+
+/// The rectangle drawn while dragging.
+#[derive(Component, Copy, Clone, Default, Debug)]
+pub struct SelectionBox {
+    start: Vec2,
+}
+
+type Selectable = Or<(With<PlanetarySystem>, With<Fleet>)>;
+
+/// Whether the selection is added to, rather than replaced.
+pub fn is_adding(keys: &ButtonInput<KeyCode>) -> bool {
+    keys.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
+}
+
+/// Select the clicked object, or clear the selection when clicking on
+/// nothing.
+/// # Schedule
+/// [Update], in [crate::AppState::InGame].
+pub fn click(
+    mut er_click: EventReader<Pointer<Click>>,
+    mut ew_changed: EventWriter<SelectionChanged>,
+    mut selection: ResMut<Selection>,
+    buttons: Res<MouseButtons>,
+    keys: Res<ButtonInput<KeyCode>>,
+    q_object: Query<&ObjectId, Selectable>,
+) {
+    let mut clicked_anything = false;
+    let mut target = None;
+    for click in er_click.read() {
+        if click.button != PointerButton::Primary {
+            continue;
+        }
+        clicked_anything = true;
+        if let Ok(id) = q_object.get(click.target) {
+            target.get_or_insert(ObjectRef::new(click.target, *id));
+        }
+    }
+
+    let add = is_adding(&keys);
+    let changed = match target {
+        Some(target) => selection.select([target], add),
+        // a click on the UI keeps the selection.
+        None if buttons.left.clicked() && !clicked_anything && !add => selection.select([], false),
+        None => false,
+    };
+    if changed {
+        ew_changed.send(SelectionChanged);
+    }
+}
+
+/// Draw the selection rectangle while dragging, and select the fleets in
+/// it once released.
+/// # Schedule
+/// [Update], in [crate::AppState::InGame].
+#[allow(clippy::too_many_arguments)]
+pub fn drag(
+    mut commands: Commands,
+    mut er_down: EventReader<Pointer<Down>>,
+    mut ew_changed: EventWriter<SelectionChanged>,
+    mut selection: ResMut<Selection>,
+    mut q_box: Query<(Entity, &SelectionBox, &mut Style)>,
+    mut press_position: Local<Vec2>,
+    mut pressed_on_ui: Local<bool>,
+    q_node: Query<(), With<Node>>,
+    buttons: Res<MouseButtons>,
+    motion: Res<MouseMotion>,
+    keys: Res<ButtonInput<KeyCode>>,
+    q_camera: Query<(&Camera, &GlobalTransform), With<PrimaryCamera>>,
+    q_fleet: Query<(Entity, &ObjectId, &GlobalTransform), With<Fleet>>,
+) {
+    let left = &buttons.left;
+    let pressed_nodes = er_down
+        .read()
+        .filter(|down| down.button == PointerButton::Primary)
+        .any(|down| q_node.contains(down.target));
+    if left.just_pressed() {
+        *press_position = motion.position;
+        // dragging on the UI, such as the minimap, does not select.
+        *pressed_on_ui = pressed_nodes;
+    }
+
+    if left.drag_started() && !*pressed_on_ui {
+        commands.spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    border: UiRect::all(Val::Px(1.0)),
+                    ..default()
+                },
+                border_color: tailwind::SKY_400.into(),
+                background_color: Color::from(tailwind::SKY_400.with_alpha(0.1)).into(),
+                ..default()
+            },
+            SelectionBox {
+                start: *press_position,
+            },
+            Pickable::IGNORE,
+        ));
+        return;
+    }
+
+    let Ok((entity, selection_box, mut style)) = q_box.get_single_mut() else {
+        return;
+    };
+    let rect = Rect::from_corners(selection_box.start, motion.position);
+
+    if left.dragging() {
+        style.left = Val::Px(rect.min.x);
+        style.top = Val::Px(rect.min.y);
+        style.width = Val::Px(rect.width());
+        style.height = Val::Px(rect.height());
+        return;
+    }
+
+    commands.entity(entity).despawn_recursive();
+    let Ok((camera, camera_transform)) = q_camera.get_single() else {
+        return;
+    };
+    let fleets = q_fleet.iter().filter_map(|(entity, id, transform)| {
+        camera
+            .world_to_viewport(camera_transform, transform.translation())
+            .filter(|position| rect.contains(*position))
+            .map(|_| ObjectRef::new(entity, *id))
+    });
+    if selection.select(fleets, is_adding(&keys)) {
+        ew_changed.send(SelectionChanged);
+    }
+}
+
+/// Order the selected fleets of the player to move to the right-clicked
+/// planetary system.
+/// # Schedule
+/// [Update], in [crate::AppState::InGame].
+#[allow(clippy::too_many_arguments)]
+pub fn order_move(
+    mut er_click: EventReader<Pointer<Click>>,
+    mut ew_effect: EventWriter<Effect>,
+    mut pending: ResMut<PendingCommands>,
+    selection: Res<Selection>,
+    session: Option<Res<NetSession>>,
+    q_system: Query<&ObjectId, With<PlanetarySystem>>,
+    q_fleet: Query<&Owner, With<Fleet>>,
+    q_empire: Query<&PlayerControlled, With<Empire>>,
+) {
+    let local = session.map_or(PlayerId::default(), |session| session.local);
+    let is_local = |owner: &Owner| {
+        q_empire
+            .get(owner.0.entity)
+            .is_ok_and(|player| player.0 == local)
+    };
+
+    for click in er_click.read() {
+        if click.button != PointerButton::Secondary {
+            continue;
+        }
+        let Ok(target) = q_system.get(click.target) else {
+            continue;
+        };
+
+        let mut ordered = false;
+        for fleet in selection.0.iter() {
+            if q_fleet.get(fleet.entity).is_ok_and(is_local) {
+                pending.0.push(GameCommand::MoveFleet {
+                    fleet: fleet.object_id,
+                    target: *target,
+                });
+                ordered = true;
+            }
+        }
+        if ordered {
+            ew_effect.send(Effect::MoveOrder);
+        }
+    }
+}
+
+/// Forget objects that no longer exist.
+/// # Schedule
+/// [Update], in [crate::AppState::InGame].
+pub fn cleanup(
+    mut ew_changed: EventWriter<SelectionChanged>,
+    mut selection: ResMut<Selection>,
+    q_object: Query<(), Selectable>,
+) {
+    if selection
+        .0
+        .iter()
+        .all(|object| q_object.contains(object.entity))
+    {
+        return;
+    }
+    selection
+        .0
+        .retain(|object| q_object.contains(object.entity));
+    ew_changed.send(SelectionChanged);
+}
+
+/// Clear the selection when leaving the game.
+/// # Schedule
+/// Exit [crate::AppState::InGame].
+pub fn reset(
+    mut commands: Commands,
+    mut selection: ResMut<Selection>,
+    q_box: Query<Entity, With<SelectionBox>>,
+) {
+    selection.0.clear();
+    for entity in q_box.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Circle the selected objects.
+/// # Schedule
+/// [Update], in [crate::AppState::InGame].
+pub fn highlight(mut gizmos: Gizmos, selection: Res<Selection>, q_object: Query<&GlobalTransform>) {
+    for object in selection.0.iter() {
+        if let Ok(transform) = q_object.get(object.entity) {
+            gizmos.circle(transform.translation(), Dir3::Z, 1.0, tailwind::SKY_400);
+        }
+    }
+}
@@ -1,10 +1,43 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-#[derive(Resource, Copy, Clone, Debug)]
-pub struct AudioSettings {}
+/// The groups of sounds with their own volume.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Channel {
+    Music,
+    Effects,
+    Ui,
+}
+
+#[derive(Resource, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AudioSettings {
+    /// The volume of all channels, from 0 to 1.
+    pub master: f32,
+    pub music: f32,
+    pub effects: f32,
+    pub ui: f32,
+}
 
 impl Default for AudioSettings {
     fn default() -> Self {
-        Self {}
+        Self {
+            master: 1.0,
+            music: 0.6,
+            effects: 1.0,
+            ui: 0.8,
+        }
+    }
+}
+
+impl AudioSettings {
+    /// The volume a channel is played at, including the master volume.
+    pub fn volume(&self, channel: Channel) -> f32 {
+        let volume = match channel {
+            Channel::Music => self.music,
+            Channel::Effects => self.effects,
+            Channel::Ui => self.ui,
+        };
+        (self.master * volume).clamp(0.0, 1.0)
     }
 }
@@ -0,0 +1,129 @@
+//! The settings file, stored as RON in the config directory of the
+//! platform.
+//!
+//! Keys missing from the file take their default value, so that older files
+//! keep working when settings are added. A file that cannot be read falls
+//! back to the defaults, and is only overwritten once a setting changes.
+
+use super::{AudioSettings, InputSettings, VideoSettings};
+use bevy::prelude::*;
+use directories::ProjectDirs;
+use serde::{Deserialize, Serialize};
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// The version of the settings format. Older files are migrated, newer ones
+/// are ignored.
+pub const SETTINGS_VERSION: u32 = 1;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SettingsFile {
+    pub version: u32,
+    pub audio: AudioSettings,
+    pub video: VideoSettings,
+    pub input: InputSettings,
+}
+
+impl Default for SettingsFile {
+    fn default() -> Self {
+        Self {
+            version: SETTINGS_VERSION,
+            audio: AudioSettings::default(),
+            video: VideoSettings::default(),
+            input: InputSettings::default(),
+        }
+    }
+}
+
+impl SettingsFile {
+    /// Read the settings at `path`, or the defaults if they are missing or
+    /// invalid.
+    pub fn load(path: &Path) -> Self {
+        let text = match std::fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == ErrorKind::NotFound => return Self::default(),
+            Err(error) => {
+                warn!("Fail to read settings {:?}, use defaults: {}", path, error);
+                return Self::default();
+            }
+        };
+        let file: Self = match ron::from_str(&text) {
+            Ok(file) => file,
+            Err(error) => {
+                warn!("Invalid settings {:?}, use defaults: {}", path, error);
+                return Self::default();
+            }
+        };
+        if file.version > SETTINGS_VERSION {
+            warn!(
+                "Settings {:?} are of a newer version {}, use defaults.",
+                path, file.version
+            );
+            return Self::default();
+        }
+        file.migrate()
+    }
+
+    /// Bring settings of an older version to the current one.
+    fn migrate(mut self) -> Self {
+        // no older version yet, the missing keys already took defaults.
+        self.version = SETTINGS_VERSION;
+        self
+    }
+
+    fn to_text(&self) -> String {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .expect("Fail to serialize settings.")
+    }
+}
+
+/// Where the settings are stored, and what was last read or written there.
+#[derive(Resource, Clone, Debug)]
+pub struct SettingsStorage {
+    /// `None` if the platform has no config directory.
+    pub path: Option<PathBuf>,
+    saved: String,
+}
+
+impl SettingsStorage {
+    /// Load the settings from the config directory of the platform.
+    pub fn load() -> (Self, SettingsFile) {
+        let path = ProjectDirs::from("", "", "stellaris")
+            .map(|dirs| dirs.config_dir().join("settings.ron"));
+        let file = match &path {
+            Some(path) => SettingsFile::load(path),
+            None => {
+                warn!("Fail to find the config directory, settings will not be saved.");
+                SettingsFile::default()
+            }
+        };
+        let storage = Self {
+            path,
+            saved: file.to_text(),
+        };
+        (storage, file)
+    }
+
+    /// Write the settings, if they differ from the stored ones.
+    pub fn save(&mut self, file: &SettingsFile) {
+        let text = file.to_text();
+        if text == self.saved {
+            return;
+        }
+        let Some(path) = &self.path else {
+            return;
+        };
+        let result = path
+            .parent()
+            .map_or(Ok(()), std::fs::create_dir_all)
+            .and_then(|_| std::fs::write(path, &text));
+        match result {
+            Ok(()) => {
+                info!("Settings saved to {:?}.", path);
+                self.saved = text;
+            }
+            Err(error) => warn!("Fail to save settings {:?}: {}", path, error),
+        }
+    }
+}
@@ -1,9 +1,300 @@
 use bevy::prelude::*;
+use bevy::utils::HashMap;
+use serde::{Deserialize, Serialize};
 
-#[derive(Resource, Copy, Clone, Debug)]
+/// The actions that can be bound to keys.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Action {
+    PanUp,
+    PanDown,
+    PanLeft,
+    PanRight,
+    RotateLeft,
+    RotateRight,
+    ZoomIn,
+    ZoomOut,
+    FocusSelection,
+    ToggleTopDown,
+    QuickSave,
+    Back,
+}
+
+impl Action {
+    pub const ALL: [Action; 12] = [
+        Action::PanUp,
+        Action::PanDown,
+        Action::PanLeft,
+        Action::PanRight,
+        Action::RotateLeft,
+        Action::RotateRight,
+        Action::ZoomIn,
+        Action::ZoomOut,
+        Action::FocusSelection,
+        Action::ToggleTopDown,
+        Action::QuickSave,
+        Action::Back,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::PanUp => "Pan Up",
+            Action::PanDown => "Pan Down",
+            Action::PanLeft => "Pan Left",
+            Action::PanRight => "Pan Right",
+            Action::RotateLeft => "Rotate Left",
+            Action::RotateRight => "Rotate Right",
+            Action::ZoomIn => "Zoom In",
+            Action::ZoomOut => "Zoom Out",
+            Action::FocusSelection => "Focus Selection",
+            Action::ToggleTopDown => "Top-Down View",
+            Action::QuickSave => "Quick Save",
+            Action::Back => "Back",
+        }
+    }
+}
+
+/// The number of keys an action can be bound to.
+pub const BINDING_SLOTS: usize = 2;
+
+/// The keys bound to each [Action]. A key may be bound to several actions,
+/// these are reported by [KeyBindings::conflicts].
+///
+/// Actions missing from a settings file keep their default keys.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "BindingMap", into = "BindingMap")]
+pub struct KeyBindings(BindingMap);
+
+type BindingMap = HashMap<Action, [Option<KeyCode>; BINDING_SLOTS]>;
+
+impl From<BindingMap> for KeyBindings {
+    fn from(map: BindingMap) -> Self {
+        let mut bindings = Self::default();
+        bindings.0.extend(map);
+        bindings
+    }
+}
+
+impl From<KeyBindings> for BindingMap {
+    fn from(bindings: KeyBindings) -> Self {
+        bindings.0
+    }
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self(HashMap::from_iter([
+            (Action::PanUp, [Some(KeyCode::KeyW), Some(KeyCode::ArrowUp)]),
+            (
+                Action::PanDown,
+                [Some(KeyCode::KeyS), Some(KeyCode::ArrowDown)],
+            ),
+            (
+                Action::PanLeft,
+                [Some(KeyCode::KeyA), Some(KeyCode::ArrowLeft)],
+            ),
+            (
+                Action::PanRight,
+                [Some(KeyCode::KeyD), Some(KeyCode::ArrowRight)],
+            ),
+            (Action::RotateLeft, [Some(KeyCode::KeyQ), None]),
+            (Action::RotateRight, [Some(KeyCode::KeyE), None]),
+            (
+                Action::ZoomIn,
+                [Some(KeyCode::Equal), Some(KeyCode::NumpadAdd)],
+            ),
+            (
+                Action::ZoomOut,
+                [Some(KeyCode::Minus), Some(KeyCode::NumpadSubtract)],
+            ),
+            (Action::FocusSelection, [Some(KeyCode::KeyF), None]),
+            (Action::ToggleTopDown, [Some(KeyCode::KeyM), None]),
+            (Action::QuickSave, [Some(KeyCode::F5), None]),
+            (Action::Back, [Some(KeyCode::Escape), None]),
+        ]))
+    }
+}
+
+impl KeyBindings {
+    /// The keys bound to an action.
+    pub fn keys(&self, action: Action) -> [Option<KeyCode>; BINDING_SLOTS] {
+        self.0.get(&action).copied().unwrap_or_default()
+    }
+
+    /// Bind, or unbind with `None`, a slot of an action.
+    pub fn bind(&mut self, action: Action, slot: usize, key: Option<KeyCode>) {
+        self.0.entry(action).or_default()[slot] = key;
+    }
+
+    pub fn pressed(&self, action: Action, keys: &ButtonInput<KeyCode>) -> bool {
+        self.keys(action)
+            .into_iter()
+            .flatten()
+            .any(|key| keys.pressed(key))
+    }
+
+    pub fn just_pressed(&self, action: Action, keys: &ButtonInput<KeyCode>) -> bool {
+        self.keys(action)
+            .into_iter()
+            .flatten()
+            .any(|key| keys.just_pressed(key))
+    }
+
+    /// -1, 0 or 1, from the negative and positive action of an axis.
+    pub fn axis(&self, negative: Action, positive: Action, keys: &ButtonInput<KeyCode>) -> f32 {
+        f32::from(self.pressed(positive, keys)) - f32::from(self.pressed(negative, keys))
+    }
+
+    /// The actions a key is bound to.
+    pub fn actions(&self, key: KeyCode) -> Vec<Action> {
+        Action::ALL
+            .into_iter()
+            .filter(|action| self.keys(*action).contains(&Some(key)))
+            .collect()
+    }
+
+    /// The keys bound to more than one action, with those actions.
+    pub fn conflicts(&self) -> Vec<(KeyCode, Vec<Action>)> {
+        let mut keys: Vec<KeyCode> = self.0.values().flatten().flatten().copied().collect();
+        keys.sort();
+        keys.dedup();
+        keys.into_iter()
+            .map(|key| (key, self.actions(key)))
+            .filter(|(_, actions)| actions.len() > 1)
+            .collect()
+    }
+}
+
+/// A readable name of a key.
+pub fn key_name(key: KeyCode) -> String {
+    let name = format!("{:?}", key);
+    for prefix in ["Key", "Digit"] {
+        if let Some(name) = name.strip_prefix(prefix) {
+            return name.to_string();
+        }
+    }
+    name
+}
+
+/// When the camera scrolls with the pointer on the border of the window.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EdgeScrollMode {
+    Always,
+    #[default]
+    WhenFocused,
+    Never,
+}
+
+impl EdgeScrollMode {
+    pub const ALL: [EdgeScrollMode; 3] = [
+        EdgeScrollMode::Always,
+        EdgeScrollMode::WhenFocused,
+        EdgeScrollMode::Never,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EdgeScrollMode::Always => "Always",
+            EdgeScrollMode::WhenFocused => "When Focused",
+            EdgeScrollMode::Never => "Never",
+        }
+    }
+}
+
+/// How the scroll speed grows as the pointer goes deeper into the border.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SpeedCurve {
+    /// Full speed anywhere in the border.
+    Constant,
+    #[default]
+    Linear,
+    Quadratic,
+}
+
+impl SpeedCurve {
+    pub const ALL: [SpeedCurve; 3] = [
+        SpeedCurve::Constant,
+        SpeedCurve::Linear,
+        SpeedCurve::Quadratic,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpeedCurve::Constant => "Constant",
+            SpeedCurve::Linear => "Linear",
+            SpeedCurve::Quadratic => "Quadratic",
+        }
+    }
+
+    /// The part of the full speed, for a depth into the border from 0 at
+    /// its inner side to 1 at the edge of the window.
+    pub fn apply(&self, depth: f32) -> f32 {
+        let depth = depth.clamp(0.0, 1.0);
+        match self {
+            SpeedCurve::Constant => f32::from(depth > 0.0),
+            SpeedCurve::Linear => depth,
+            SpeedCurve::Quadratic => depth * depth,
+        }
+    }
+}
+
+/// The camera scrolling with the pointer on the border of the window.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EdgeScroll {
+    pub mode: EdgeScrollMode,
+    /// The width of the border, in pixels.
+    pub border_width: f32,
+    pub curve: SpeedCurve,
+    /// The speed at the lowest height, in ly/s.
+    pub base_speed: f32,
+    /// The speed added per ly of height.
+    pub height_factor: f32,
+    /// The time to reach full speed, in seconds. Scrolling starts at
+    /// [EdgeScroll::START_SPEED] of it.
+    pub acceleration_time: f32,
+}
+
+impl EdgeScroll {
+    /// The part of the full speed when scrolling starts.
+    pub const START_SPEED: f32 = 0.2;
+
+    /// The part of the full speed after scrolling for `time` seconds.
+    pub fn acceleration(&self, time: f32) -> f32 {
+        if self.acceleration_time <= 0.0 {
+            return 1.0;
+        }
+        let t = (time / self.acceleration_time).min(1.0);
+        Self::START_SPEED + (1.0 - Self::START_SPEED) * t
+    }
+}
+
+impl Default for EdgeScroll {
+    fn default() -> Self {
+        Self {
+            mode: EdgeScrollMode::default(),
+            border_width: 8.0,
+            curve: SpeedCurve::default(),
+            base_speed: 100.0,
+            height_factor: 1.0,
+            acceleration_time: 0.5,
+        }
+    }
+}
+
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct InputSettings {
     pub mouse_motion_sensitivity: f32,
     pub mouse_scroll_sensitivity: f32,
+    /// The longest time between two clicks of a double-click, in seconds.
+    pub double_click_time: f32,
+    /// How far the pointer moves before a press becomes a drag, in pixels.
+    pub drag_threshold: f32,
+    /// The speed of the pointer moved by a gamepad stick, in pixels per
+    /// second.
+    pub gamepad_cursor_speed: f32,
+    pub edge_scroll: EdgeScroll,
+    pub bindings: KeyBindings,
 }
 
 impl Default for InputSettings {
@@ -11,6 +302,11 @@ impl Default for InputSettings {
         Self {
             mouse_motion_sensitivity: 1.0,
             mouse_scroll_sensitivity: 1.0,
+            double_click_time: 0.4,
+            drag_threshold: 4.0,
+            gamepad_cursor_speed: 800.0,
+            edge_scroll: EdgeScroll::default(),
+            bindings: KeyBindings::default(),
         }
     }
 }
@@ -1,12 +1,17 @@
 //! This module contain the setting related to input and ui.
+//!
+//! The settings are loaded from their file when the plugin is built, see
+//! [file].
 
 mod audio;
+mod file;
 mod input;
 mod video;
 
-pub use audio::AudioSettings;
-pub use input::InputSettings;
-pub use video::VideoSettings;
+pub use audio::{AudioSettings, Channel};
+pub use file::{SettingsFile, SettingsStorage, SETTINGS_VERSION};
+pub use input::{key_name, Action, EdgeScrollMode, InputSettings, KeyBindings, SpeedCurve};
+pub use video::{DisplayMode, Tonemapper, VideoSettings, RESOLUTIONS};
 
 use bevy::prelude::*;
 
@@ -14,8 +19,15 @@ pub struct SettingsPlugin;
 
 impl Plugin for SettingsPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(AudioSettings::default())
-            .insert_resource(InputSettings::default())
-            .insert_resource(VideoSettings::default());
+        let (storage, file) = SettingsStorage::load();
+        app.insert_resource(file.audio)
+            .insert_resource(file.input)
+            .insert_resource(file.video)
+            .insert_resource(storage)
+            .add_systems(
+                PreUpdate,
+                (video::apply_window, video::apply_camera)
+                    .run_if(resource_changed::<VideoSettings>),
+            );
     }
 }
@@ -1,10 +1,179 @@
+use crate::ui::PrimaryCamera;
+use bevy::core_pipeline::{bloom::BloomSettings, tonemapping::Tonemapping};
 use bevy::prelude::*;
+use bevy::window::{PresentMode, PrimaryWindow, WindowMode};
+use serde::{Deserialize, Serialize};
 
-#[derive(Resource, Copy, Clone, Debug)]
-pub struct VideoSettings {}
+/// How the window takes the screen.
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    #[default]
+    Windowed,
+    /// A fullscreen window without decorations, at the resolution of the
+    /// screen.
+    Borderless,
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub const ALL: [DisplayMode; 3] = [
+        DisplayMode::Windowed,
+        DisplayMode::Borderless,
+        DisplayMode::Fullscreen,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DisplayMode::Windowed => "Windowed",
+            DisplayMode::Borderless => "Borderless",
+            DisplayMode::Fullscreen => "Fullscreen",
+        }
+    }
+
+    pub fn window_mode(&self) -> WindowMode {
+        match self {
+            DisplayMode::Windowed => WindowMode::Windowed,
+            DisplayMode::Borderless => WindowMode::BorderlessFullscreen,
+            DisplayMode::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+/// The tonemapping of the [PrimaryCamera].
+#[derive(Copy, Clone, Default, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Tonemapper {
+    None,
+    Reinhard,
+    AcesFitted,
+    AgX,
+    #[default]
+    TonyMcMapface,
+    BlenderFilmic,
+}
+
+impl Tonemapper {
+    pub const ALL: [Tonemapper; 6] = [
+        Tonemapper::None,
+        Tonemapper::Reinhard,
+        Tonemapper::AcesFitted,
+        Tonemapper::AgX,
+        Tonemapper::TonyMcMapface,
+        Tonemapper::BlenderFilmic,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Tonemapper::None => "None",
+            Tonemapper::Reinhard => "Reinhard",
+            Tonemapper::AcesFitted => "ACES",
+            Tonemapper::AgX => "AgX",
+            Tonemapper::TonyMcMapface => "Tony McMapface",
+            Tonemapper::BlenderFilmic => "Blender Filmic",
+        }
+    }
+
+    pub fn tonemapping(&self) -> Tonemapping {
+        match self {
+            Tonemapper::None => Tonemapping::None,
+            Tonemapper::Reinhard => Tonemapping::Reinhard,
+            Tonemapper::AcesFitted => Tonemapping::AcesFitted,
+            Tonemapper::AgX => Tonemapping::AgX,
+            Tonemapper::TonyMcMapface => Tonemapping::TonyMcMapface,
+            Tonemapper::BlenderFilmic => Tonemapping::BlenderFilmic,
+        }
+    }
+}
+
+/// The resolutions offered by the settings page.
+pub const RESOLUTIONS: [UVec2; 6] = [
+    UVec2::new(1280, 720),
+    UVec2::new(1366, 768),
+    UVec2::new(1600, 900),
+    UVec2::new(1920, 1080),
+    UVec2::new(2560, 1440),
+    UVec2::new(3840, 2160),
+];
+
+#[derive(Resource, Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VideoSettings {
+    pub display_mode: DisplayMode,
+    /// The size of the window, in logical pixels. Only used by
+    /// [DisplayMode::Windowed], fullscreen modes take the size of the screen.
+    pub resolution: UVec2,
+    pub vsync: bool,
+    /// The intensity of the bloom, no bloom at 0.
+    pub bloom_intensity: f32,
+    pub tonemapper: Tonemapper,
+    /// The scale of the menus.
+    pub ui_scale: f32,
+}
 
 impl Default for VideoSettings {
     fn default() -> Self {
-        Self {}
+        Self {
+            display_mode: DisplayMode::default(),
+            resolution: RESOLUTIONS[0],
+            vsync: true,
+            bloom_intensity: BloomSettings::NATURAL.intensity,
+            tonemapper: Tonemapper::default(),
+            ui_scale: 1.0,
+        }
+    }
+}
+
+impl VideoSettings {
+    pub fn bloom(&self) -> Option<BloomSettings> {
+        (self.bloom_intensity > 0.0).then_some(BloomSettings {
+            intensity: self.bloom_intensity,
+            ..BloomSettings::NATURAL
+        })
     }
 }
+
+/// Apply the display mode, resolution and vsync to the primary window. The
+/// fullscreen modes keep the resolution of the screen.
+/// # Schedule
+/// [PreUpdate], when [VideoSettings] changes.
+pub fn apply_window(
+    settings: Res<VideoSettings>,
+    mut q_window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    let Ok(mut window) = q_window.get_single_mut() else {
+        return;
+    };
+    let mode = settings.display_mode.window_mode();
+    if window.mode != mode {
+        window.mode = mode;
+    }
+    let resolution = settings.resolution.as_vec2();
+    if settings.display_mode == DisplayMode::Windowed && window.resolution.size() != resolution {
+        window.resolution.set(resolution.x, resolution.y);
+    }
+    let present_mode = if settings.vsync {
+        PresentMode::AutoVsync
+    } else {
+        PresentMode::AutoNoVsync
+    };
+    if window.present_mode != present_mode {
+        window.present_mode = present_mode;
+    }
+}
+
+/// Apply the bloom and tonemapping to the [PrimaryCamera].
+/// # Schedule
+/// [PreUpdate], when [VideoSettings] changes.
+pub fn apply_camera(
+    mut commands: Commands,
+    settings: Res<VideoSettings>,
+    mut q_camera: Query<(Entity, &mut Tonemapping), With<PrimaryCamera>>,
+) {
+    let Ok((camera, mut tonemapping)) = q_camera.get_single_mut() else {
+        return;
+    };
+    *tonemapping = settings.tonemapper.tonemapping();
+    match settings.bloom() {
+        Some(bloom) => commands.entity(camera).insert(bloom),
+        None => commands.entity(camera).remove::<BloomSettings>(),
+    };
+}
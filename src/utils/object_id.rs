@@ -1,8 +1,9 @@
 //! Id for game objects.
 
 use bevy::prelude::{Component, Entity};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
-use uuid::Uuid;
+use uuid::{Builder, Uuid};
 
 /// A stable unique id for all objects in the game
 #[derive(Component, Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -14,6 +15,21 @@ impl Default for ObjectId {
     }
 }
 
+impl ObjectId {
+    /// Create an id from the given random generator. Game objects
+    /// created during map generation must use this, so that every
+    /// peer generating the same map agrees on all ids.
+    pub fn from_rng(rng: &mut impl Rng) -> Self {
+        Self(Builder::from_random_bytes(rng.gen()).into_uuid())
+    }
+
+    /// A short designation for the player, the first four hex digits of
+    /// the id, such as `A3F9`. It is the same on every peer.
+    pub fn designation(&self) -> String {
+        format!("{:04X}", self.0.as_u128() >> 112)
+    }
+}
+
 /// A struct to record [ObjectId] and [Entity] info,
 /// usually useful or record relation between
 /// game objects.
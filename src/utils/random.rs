@@ -44,11 +44,15 @@ impl RngExt for Pcg64Mcg {
 }
 
 pub fn default_rng() -> Pcg64Mcg {
+    Pcg64Mcg::new(random_seed())
+}
+
+/// A non-deterministic seed, used when the user does not provide one.
+pub fn random_seed() -> u128 {
     let mut random_seed: u128 = thread_rng().next_u64() as u128;
     random_seed = random_seed << 64;
     random_seed += thread_rng().next_u64() as u128;
-
-    Pcg64Mcg::new(random_seed)
+    random_seed
 }
 
 pub fn beta_params(mu: f32, sigma: f32) -> (f32, f32) {